@@ -0,0 +1,562 @@
+use crate::error::{AppError, Result};
+use crate::resources::parse_propagation_policy;
+use crate::utils::validation;
+use kube::api::PropagationPolicy;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+const DEFAULT_KUBE_TIMEOUT_SECONDS: u64 = 30;
+const DEFAULT_MAX_KUBE_TIMEOUT_SECONDS: u64 = 300;
+const DEFAULT_NAMESPACE_MAX_CONCURRENT_MUTATIONS: usize = 8;
+const DEFAULT_NAMESPACE_MAX_QUEUED_MUTATIONS: usize = 32;
+const DEFAULT_RETRY_AFTER_SECONDS: u64 = 5;
+const DEFAULT_RESOURCE_COUNT_INTERVAL_SECONDS: u64 = 60;
+const DEFAULT_QUARANTINE_GRACE_PERIOD_SECONDS: u64 = 7 * 24 * 3600;
+const DEFAULT_TCP_BACKLOG: u32 = 1024;
+const DEFAULT_MAX_PAGE_SIZE: u32 = 500;
+const DEFAULT_WORKSPACE_SIZE: &str = "10Gi";
+const DEFAULT_GPU_TOLERATION_KEY: &str = "nvidia.com/gpu";
+const DEFAULT_DEBUG_POD_IMAGE: &str = "busybox:1.36";
+const DEFAULT_NAMESPACE: &str = "default";
+const DEFAULT_SHUTDOWN_GRACE_SECONDS: u64 = 30;
+const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+const DEFAULT_DEBUG_POD_DEADLINE_SECONDS: u64 = 3600;
+/// PostgreSQL parameters managed by the CNPG operator; overriding them breaks replication, archiving or startup.
+const DEFAULT_POSTGRES_PARAMETER_DENYLIST: &[&str] = &[
+    "archive_command",
+    "archive_mode",
+    "config_file",
+    "data_directory",
+    "full_page_writes",
+    "hba_file",
+    "hot_standby",
+    "ident_file",
+    "listen_addresses",
+    "port",
+    "primary_conninfo",
+    "primary_slot_name",
+    "restore_command",
+    "ssl",
+    "ssl_ca_file",
+    "ssl_cert_file",
+    "ssl_key_file",
+    "unix_socket_directories",
+    "wal_level",
+    "wal_log_hints",
+];
+
+/// Service configuration read from environment variables at startup.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Upper bound for a single Kubernetes API call (`KUBE_TIMEOUT_SECONDS`).
+    pub kube_timeout: Duration,
+    /// Largest timeout a request may ask for with `?timeout=` (`MAX_KUBE_TIMEOUT_SECONDS`).
+    pub max_kube_timeout: Duration,
+    /// Concurrent create/update/delete calls allowed per namespace (`NAMESPACE_MAX_CONCURRENT_MUTATIONS`).
+    pub namespace_max_concurrent_mutations: usize,
+    /// Mutations allowed to wait for a slot before returning 429 (`NAMESPACE_MAX_QUEUED_MUTATIONS`).
+    pub namespace_max_queued_mutations: usize,
+    /// Tokio worker threads (`WORKER_THREADS`). `None` uses one per CPU core.
+    pub worker_threads: Option<usize>,
+    /// Pending connection queue length of the listening socket (`TCP_BACKLOG`).
+    pub tcp_backlog: u32,
+    /// Idle time before TCP keepalive probes are sent on client connections (`TCP_KEEPALIVE_SECONDS`). `None` disables keepalive.
+    pub tcp_keepalive: Option<Duration>,
+    /// Base path for every route except `/health` and `/metrics` (`API_PREFIX`, e.g. `/api/v1`). Empty serves them at the root.
+    pub api_prefix: String,
+    /// Start in read-only mode, rejecting create/update/delete with 503 (`READ_ONLY`). Can be toggled at runtime.
+    pub read_only: bool,
+    /// Return mutating endpoints' results as before, without the `OperationResponse` envelope (`LEGACY_RESPONSES`).
+    pub legacy_responses: bool,
+    /// Bearer token for the `/admin` endpoints (`ADMIN_TOKEN`). `None` disables them.
+    pub admin_token: Option<String>,
+    /// Delay suggested to clients in the `Retry-After` header of 429 and 503 responses (`RETRY_AFTER_SECONDS`).
+    pub retry_after: Duration,
+    /// Record Kubernetes Events for creates, updates and failed workspace PVCs (`EMIT_EVENTS`).
+    pub emit_events: bool,
+    /// Reporting instance of those events (`POD_NAME`, falling back to `HOSTNAME`).
+    pub event_instance: Option<String>,
+    /// Largest `limit` passed to the API server for a list page (`MAX_PAGE_SIZE`); larger requests are clamped.
+    pub max_page_size: u32,
+    /// Namespaces listed by `GET /namespaces` (`ALLOWED_NAMESPACES`, comma-separated). `None` lists all.
+    /// Only scopes listings and background scans; requests naming another namespace are not rejected.
+    pub allowed_namespaces: Option<Vec<String>>,
+    /// Namespaces with per-namespace resource-count metrics (`METRICS_NAMESPACES`, comma-separated).
+    /// Defaults to `ALLOWED_NAMESPACES`; with neither set, the counts are not collected.
+    pub metrics_namespaces: Option<Vec<String>>,
+    /// How often the resource-count metrics are refreshed (`RESOURCE_COUNT_INTERVAL_SECONDS`).
+    pub resource_count_interval: Duration,
+    /// How much of internal and Kubernetes error messages is returned to clients (`ERROR_DETAIL`).
+    pub error_detail: ErrorDetail,
+    /// Storage classes clients may request for clusters and notebook workspaces (`ALLOWED_STORAGE_CLASSES`,
+    /// comma-separated). `None` allows any class.
+    pub allowed_storage_classes: Option<Vec<String>>,
+    /// Workspace PVC size used when a notebook asks for a workspace without a size (`DEFAULT_WORKSPACE_SIZE`).
+    pub default_workspace_size: String,
+    /// Resource types to serve (`ENABLED_RESOURCES`, comma-separated). `None` enables all built-in types.
+    pub enabled_resources: Option<Vec<String>>,
+    /// Service account of notebooks that do not name one (`DEFAULT_NOTEBOOK_SERVICE_ACCOUNT`). `None` leaves it
+    /// to Kubernetes, i.e. the namespace's `default` service account.
+    pub default_notebook_service_account: Option<String>,
+    /// Taint key tolerated automatically by notebooks that request a GPU (`GPU_TOLERATION_KEY`).
+    pub gpu_toleration_key: String,
+    /// Most GPUs a single notebook may request (`MAX_GPU_PER_NOTEBOOK`). `None` imposes no cap.
+    pub max_gpu_per_notebook: Option<u32>,
+    /// PostgreSQL parameters clients may not set (`POSTGRES_PARAMETER_DENYLIST`, comma-separated, replaces the built-in list).
+    pub postgres_parameter_denylist: Vec<String>,
+    /// Accept PostgreSQL parameters missing from the built-in list of known settings (`ALLOW_UNKNOWN_POSTGRES_PARAMETERS`).
+    pub allow_unknown_postgres_parameters: bool,
+    /// Reject cluster creates and updates whose PostgreSQL parameters conflict with the instance count, instead of
+    /// returning warnings (`REJECT_INCONSISTENT_PARAMETERS`).
+    pub reject_inconsistent_parameters: bool,
+    /// Lets clients use reserved roles such as `postgres` as the database owner (`ALLOW_RESERVED_DATABASE_OWNER`).
+    pub allow_reserved_database_owner: bool,
+    /// Fail cluster creates asking for a `ServiceMonitor` when the Prometheus operator CRDs are missing,
+    /// instead of creating the cluster without one and returning a warning (`REQUIRE_SERVICE_MONITOR_CRD`).
+    pub require_service_monitor_crd: bool,
+    /// `VolumeSnapshotClass` for workspace snapshots (`VOLUME_SNAPSHOT_CLASS`). `None` uses the cluster default class.
+    pub volume_snapshot_class: Option<String>,
+    /// Idle time after which the notebook controller culls notebooks (`NOTEBOOK_CULL_IDLE_MINUTES`, the controller's
+    /// `CULL_IDLE_TIME`). `None` means culling is not configured and no countdown is reported.
+    pub notebook_cull_idle_time: Option<Duration>,
+    /// Which image references clients may use for notebooks and CNPG upgrades (`FORBID_LATEST_TAG`, `REQUIRE_IMAGE_DIGEST`).
+    pub image_tag_policy: ImageTagPolicy,
+    /// Image of notebook debug pods; needs a shell (`DEBUG_POD_IMAGE`).
+    pub debug_pod_image: String,
+    /// Lifetime of a notebook debug pod before Kubernetes stops it (`DEBUG_POD_DEADLINE_SECONDS`).
+    pub debug_pod_deadline: Duration,
+    /// How long a soft-deleted resource stays quarantined before it is deleted (`QUARANTINE_GRACE_PERIOD_SECONDS`).
+    pub quarantine_grace_period: Duration,
+    /// Delete propagation policy used when a request sets none (`DEFAULT_PROPAGATION_POLICY`). `None` leaves it to the API server.
+    pub default_propagation_policy: Option<PropagationPolicy>,
+    /// Namespace label keys copied onto the clusters and notebooks created in the namespace
+    /// (`PROPAGATE_NAMESPACE_LABELS`, comma-separated). `None` propagates nothing.
+    pub propagate_namespace_labels: Option<Vec<String>>,
+    /// Browser origins allowed to call the API (`ALLOWED_ORIGINS`, comma-separated, e.g. `https://ui.example.com`).
+    /// `None` allows no cross-origin requests.
+    pub allowed_origins: Option<Vec<String>>,
+    /// Allow cross-origin requests from any origin (`CORS_PERMISSIVE`); for local development only.
+    pub cors_permissive: bool,
+    /// Largest request body accepted (`MAX_BODY_BYTES`); larger bodies are rejected with 413.
+    pub max_body_bytes: usize,
+    /// How long in-flight requests may run after a shutdown signal before the process exits (`SHUTDOWN_GRACE_SECONDS`).
+    pub shutdown_grace_period: Duration,
+    /// Namespace used when a request names none (`DEFAULT_NAMESPACE`).
+    pub default_namespace: String,
+    /// External address of the Kubeflow gateway, e.g. `https://kubeflow.example.com` (`NOTEBOOK_BASE_URL`).
+    /// `None` reports notebook URLs as paths relative to the gateway.
+    pub notebook_base_url: Option<String>,
+}
+
+/// Verbosity of internal error messages in responses. The full message is always logged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorDetail {
+    /// Return messages verbatim; useful during development.
+    Full,
+    /// Replace messages with a generic one that carries the request id.
+    #[default]
+    Minimal,
+}
+
+/// How strictly client-supplied container images must be pinned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageTagPolicy {
+    /// Any reference, including `nginx` and `nginx:latest`.
+    #[default]
+    Any,
+    /// A tag other than `latest`, or a digest.
+    ForbidLatest,
+    /// A digest (`image@sha256:...`).
+    RequireDigest,
+}
+
+impl FromStr for ErrorDetail {
+    type Err = ();
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "full" => Ok(Self::Full),
+            "minimal" => Ok(Self::Minimal),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            kube_timeout: Duration::from_secs(DEFAULT_KUBE_TIMEOUT_SECONDS),
+            max_kube_timeout: Duration::from_secs(DEFAULT_MAX_KUBE_TIMEOUT_SECONDS),
+            namespace_max_concurrent_mutations: DEFAULT_NAMESPACE_MAX_CONCURRENT_MUTATIONS,
+            namespace_max_queued_mutations: DEFAULT_NAMESPACE_MAX_QUEUED_MUTATIONS,
+            worker_threads: None,
+            tcp_backlog: DEFAULT_TCP_BACKLOG,
+            tcp_keepalive: None,
+            api_prefix: String::new(),
+            read_only: false,
+            legacy_responses: false,
+            admin_token: None,
+            retry_after: Duration::from_secs(DEFAULT_RETRY_AFTER_SECONDS),
+            emit_events: false,
+            event_instance: None,
+            max_page_size: DEFAULT_MAX_PAGE_SIZE,
+            allowed_namespaces: None,
+            metrics_namespaces: None,
+            resource_count_interval: Duration::from_secs(DEFAULT_RESOURCE_COUNT_INTERVAL_SECONDS),
+            error_detail: ErrorDetail::default(),
+            allowed_storage_classes: None,
+            default_workspace_size: DEFAULT_WORKSPACE_SIZE.to_string(),
+            enabled_resources: None,
+            default_notebook_service_account: None,
+            gpu_toleration_key: DEFAULT_GPU_TOLERATION_KEY.to_string(),
+            max_gpu_per_notebook: None,
+            postgres_parameter_denylist: default_postgres_parameter_denylist(),
+            allow_unknown_postgres_parameters: false,
+            reject_inconsistent_parameters: false,
+            allow_reserved_database_owner: false,
+            require_service_monitor_crd: false,
+            volume_snapshot_class: None,
+            notebook_cull_idle_time: None,
+            image_tag_policy: ImageTagPolicy::default(),
+            debug_pod_image: DEFAULT_DEBUG_POD_IMAGE.to_string(),
+            debug_pod_deadline: Duration::from_secs(DEFAULT_DEBUG_POD_DEADLINE_SECONDS),
+            quarantine_grace_period: Duration::from_secs(DEFAULT_QUARANTINE_GRACE_PERIOD_SECONDS),
+            default_propagation_policy: None,
+            propagate_namespace_labels: None,
+            allowed_origins: None,
+            cors_permissive: false,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            shutdown_grace_period: Duration::from_secs(DEFAULT_SHUTDOWN_GRACE_SECONDS),
+            default_namespace: DEFAULT_NAMESPACE.to_string(),
+            notebook_base_url: None,
+        }
+    }
+}
+
+impl Config {
+    /// Namespaces covered by the resource-count metrics, if any.
+    pub fn metrics_namespaces(&self) -> Option<&[String]> {
+        self.metrics_namespaces.as_deref().or(self.allowed_namespaces.as_deref())
+    }
+
+    pub fn from_env() -> Result<Self> {
+        let kube_timeout_secs: u64 = parse_env("KUBE_TIMEOUT_SECONDS", DEFAULT_KUBE_TIMEOUT_SECONDS)?;
+        if kube_timeout_secs == 0 {
+            return Err(AppError::Config("KUBE_TIMEOUT_SECONDS must be greater than 0".to_string()));
+        }
+
+        let max_kube_timeout_secs: u64 =
+            parse_env("MAX_KUBE_TIMEOUT_SECONDS", DEFAULT_MAX_KUBE_TIMEOUT_SECONDS.max(kube_timeout_secs))?;
+        if max_kube_timeout_secs < kube_timeout_secs {
+            return Err(AppError::Config(
+                "MAX_KUBE_TIMEOUT_SECONDS must not be less than KUBE_TIMEOUT_SECONDS".to_string(),
+            ));
+        }
+
+        let namespace_max_concurrent_mutations = parse_env(
+            "NAMESPACE_MAX_CONCURRENT_MUTATIONS",
+            DEFAULT_NAMESPACE_MAX_CONCURRENT_MUTATIONS,
+        )?;
+        if namespace_max_concurrent_mutations == 0 {
+            return Err(AppError::Config(
+                "NAMESPACE_MAX_CONCURRENT_MUTATIONS must be greater than 0".to_string(),
+            ));
+        }
+
+        let worker_threads: Option<usize> = parse_optional_env("WORKER_THREADS")?;
+        if worker_threads == Some(0) {
+            return Err(AppError::Config("WORKER_THREADS must be greater than 0".to_string()));
+        }
+
+        let tcp_backlog = parse_env("TCP_BACKLOG", DEFAULT_TCP_BACKLOG)?;
+        if tcp_backlog == 0 {
+            return Err(AppError::Config("TCP_BACKLOG must be greater than 0".to_string()));
+        }
+
+        let tcp_keepalive_secs: Option<u64> = parse_optional_env("TCP_KEEPALIVE_SECONDS")?;
+        if tcp_keepalive_secs == Some(0) {
+            return Err(AppError::Config("TCP_KEEPALIVE_SECONDS must be greater than 0".to_string()));
+        }
+
+        let max_page_size = parse_env("MAX_PAGE_SIZE", DEFAULT_MAX_PAGE_SIZE)?;
+        if max_page_size == 0 {
+            return Err(AppError::Config("MAX_PAGE_SIZE must be greater than 0".to_string()));
+        }
+
+        let resource_count_interval_secs: u64 =
+            parse_env("RESOURCE_COUNT_INTERVAL_SECONDS", DEFAULT_RESOURCE_COUNT_INTERVAL_SECONDS)?;
+        if resource_count_interval_secs == 0 {
+            return Err(AppError::Config("RESOURCE_COUNT_INTERVAL_SECONDS must be greater than 0".to_string()));
+        }
+
+        let default_workspace_size: String = parse_env("DEFAULT_WORKSPACE_SIZE", DEFAULT_WORKSPACE_SIZE.to_string())?;
+        validation::validate_storage_size(&default_workspace_size)
+            .map_err(|e| AppError::Config(format!("Invalid DEFAULT_WORKSPACE_SIZE: {}", e)))?;
+
+        let allowed_storage_classes = parse_list(std::env::var("ALLOWED_STORAGE_CLASSES").ok().as_deref());
+        for class in allowed_storage_classes.iter().flatten() {
+            validation::validate_resource_name(class)
+                .map_err(|_| AppError::Config(format!("Invalid storage class '{}' in ALLOWED_STORAGE_CLASSES", class)))?;
+        }
+
+        let default_notebook_service_account: Option<String> = parse_optional_env("DEFAULT_NOTEBOOK_SERVICE_ACCOUNT")?;
+        if let Some(ref service_account) = default_notebook_service_account {
+            validation::validate_resource_name(service_account)
+                .map_err(|e| AppError::Config(format!("Invalid DEFAULT_NOTEBOOK_SERVICE_ACCOUNT: {}", e)))?;
+        }
+
+        let notebook_cull_idle_minutes: Option<u64> = parse_optional_env("NOTEBOOK_CULL_IDLE_MINUTES")?;
+        if notebook_cull_idle_minutes == Some(0) {
+            return Err(AppError::Config("NOTEBOOK_CULL_IDLE_MINUTES must be greater than 0".to_string()));
+        }
+
+        let image_tag_policy = if parse_env("REQUIRE_IMAGE_DIGEST", false)? {
+            ImageTagPolicy::RequireDigest
+        } else if parse_env("FORBID_LATEST_TAG", false)? {
+            ImageTagPolicy::ForbidLatest
+        } else {
+            ImageTagPolicy::Any
+        };
+
+        let allowed_origins = parse_list(std::env::var("ALLOWED_ORIGINS").ok().as_deref());
+        for origin in allowed_origins.iter().flatten() {
+            validate_origin(origin)?;
+        }
+
+        let max_body_bytes: usize = parse_env("MAX_BODY_BYTES", DEFAULT_MAX_BODY_BYTES)?;
+        if max_body_bytes == 0 {
+            return Err(AppError::Config("MAX_BODY_BYTES must be greater than 0".to_string()));
+        }
+
+        let default_namespace: String = parse_env("DEFAULT_NAMESPACE", DEFAULT_NAMESPACE.to_string())?;
+        validation::validate_namespace(&default_namespace)
+            .map_err(|e| AppError::Config(format!("Invalid DEFAULT_NAMESPACE: {}", e)))?;
+
+        let debug_pod_image: String = parse_env("DEBUG_POD_IMAGE", DEFAULT_DEBUG_POD_IMAGE.to_string())?;
+        validation::validate_image_name(&debug_pod_image, ImageTagPolicy::Any)
+            .map_err(|e| AppError::Config(format!("Invalid DEBUG_POD_IMAGE: {}", e)))?;
+
+        let debug_pod_deadline_secs: u64 = parse_env("DEBUG_POD_DEADLINE_SECONDS", DEFAULT_DEBUG_POD_DEADLINE_SECONDS)?;
+        if debug_pod_deadline_secs == 0 {
+            return Err(AppError::Config("DEBUG_POD_DEADLINE_SECONDS must be greater than 0".to_string()));
+        }
+
+        let quarantine_grace_period_secs: u64 =
+            parse_env("QUARANTINE_GRACE_PERIOD_SECONDS", DEFAULT_QUARANTINE_GRACE_PERIOD_SECONDS)?;
+        if quarantine_grace_period_secs == 0 {
+            return Err(AppError::Config("QUARANTINE_GRACE_PERIOD_SECONDS must be greater than 0".to_string()));
+        }
+
+        let default_propagation_policy = match std::env::var("DEFAULT_PROPAGATION_POLICY") {
+            Ok(value) if !value.trim().is_empty() => Some(
+                parse_propagation_policy(value.trim())
+                    .map_err(|_| AppError::Config(format!("Invalid value '{}' for DEFAULT_PROPAGATION_POLICY", value)))?,
+            ),
+            _ => None,
+        };
+
+        Ok(Self {
+            kube_timeout: Duration::from_secs(kube_timeout_secs),
+            max_kube_timeout: Duration::from_secs(max_kube_timeout_secs),
+            namespace_max_concurrent_mutations,
+            namespace_max_queued_mutations: parse_env(
+                "NAMESPACE_MAX_QUEUED_MUTATIONS",
+                DEFAULT_NAMESPACE_MAX_QUEUED_MUTATIONS,
+            )?,
+            worker_threads,
+            tcp_backlog,
+            tcp_keepalive: tcp_keepalive_secs.map(Duration::from_secs),
+            api_prefix: parse_api_prefix(std::env::var("API_PREFIX").ok().as_deref())?,
+            read_only: parse_env("READ_ONLY", false)?,
+            legacy_responses: parse_env("LEGACY_RESPONSES", false)?,
+            admin_token: parse_optional_env("ADMIN_TOKEN")?,
+            retry_after: Duration::from_secs(parse_env("RETRY_AFTER_SECONDS", DEFAULT_RETRY_AFTER_SECONDS)?),
+            emit_events: parse_env("EMIT_EVENTS", false)?,
+            event_instance: match parse_optional_env("POD_NAME")? {
+                Some(pod_name) => Some(pod_name),
+                None => parse_optional_env("HOSTNAME")?,
+            },
+            max_page_size,
+            allowed_namespaces: parse_namespace_list("ALLOWED_NAMESPACES", std::env::var("ALLOWED_NAMESPACES").ok().as_deref())?,
+            metrics_namespaces: parse_namespace_list("METRICS_NAMESPACES", std::env::var("METRICS_NAMESPACES").ok().as_deref())?,
+            resource_count_interval: Duration::from_secs(resource_count_interval_secs),
+            error_detail: parse_env("ERROR_DETAIL", ErrorDetail::default())?,
+            allowed_storage_classes,
+            default_workspace_size,
+            enabled_resources: parse_list(std::env::var("ENABLED_RESOURCES").ok().as_deref()),
+            default_notebook_service_account,
+            gpu_toleration_key: parse_env("GPU_TOLERATION_KEY", DEFAULT_GPU_TOLERATION_KEY.to_string())?,
+            max_gpu_per_notebook: parse_optional_env("MAX_GPU_PER_NOTEBOOK")?,
+            postgres_parameter_denylist: parse_list(std::env::var("POSTGRES_PARAMETER_DENYLIST").ok().as_deref())
+                .unwrap_or_else(default_postgres_parameter_denylist),
+            allow_unknown_postgres_parameters: parse_env("ALLOW_UNKNOWN_POSTGRES_PARAMETERS", false)?,
+            reject_inconsistent_parameters: parse_env("REJECT_INCONSISTENT_PARAMETERS", false)?,
+            allow_reserved_database_owner: parse_env("ALLOW_RESERVED_DATABASE_OWNER", false)?,
+            require_service_monitor_crd: parse_env("REQUIRE_SERVICE_MONITOR_CRD", false)?,
+            volume_snapshot_class: parse_optional_env("VOLUME_SNAPSHOT_CLASS")?,
+            notebook_cull_idle_time: notebook_cull_idle_minutes.map(|minutes| Duration::from_secs(minutes * 60)),
+            image_tag_policy,
+            debug_pod_image,
+            debug_pod_deadline: Duration::from_secs(debug_pod_deadline_secs),
+            quarantine_grace_period: Duration::from_secs(quarantine_grace_period_secs),
+            default_propagation_policy,
+            propagate_namespace_labels: parse_list(std::env::var("PROPAGATE_NAMESPACE_LABELS").ok().as_deref()),
+            allowed_origins,
+            cors_permissive: parse_env("CORS_PERMISSIVE", false)?,
+            max_body_bytes,
+            shutdown_grace_period: Duration::from_secs(parse_env("SHUTDOWN_GRACE_SECONDS", DEFAULT_SHUTDOWN_GRACE_SECONDS)?),
+            default_namespace,
+            notebook_base_url: parse_optional_env::<String>("NOTEBOOK_BASE_URL")?
+                .map(|url| url.trim_end_matches('/').to_string()),
+        })
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Loads and validates the configuration from the environment. Call once at startup.
+pub fn init() -> Result<&'static Config> {
+    let config = Config::from_env()?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// Returns the active configuration, or the defaults if `init` has not run (e.g. in tests).
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+fn default_postgres_parameter_denylist() -> Vec<String> {
+    DEFAULT_POSTGRES_PARAMETER_DENYLIST.iter().map(|key| key.to_string()).collect()
+}
+
+/// Splits a comma-separated list, dropping empty entries. An empty list is `None`.
+fn parse_list(value: Option<&str>) -> Option<Vec<String>> {
+    let items: Vec<String> = value
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    if items.is_empty() { None } else { Some(items) }
+}
+
+fn parse_namespace_list(key: &str, value: Option<&str>) -> Result<Option<Vec<String>>> {
+    let namespaces = parse_list(value).unwrap_or_default();
+
+    for namespace in &namespaces {
+        validation::validate_namespace(namespace)
+            .map_err(|_| AppError::Config(format!("Invalid namespace '{}' in {}", namespace, key)))?;
+    }
+
+    Ok(if namespaces.is_empty() { None } else { Some(namespaces) })
+}
+
+/// Normalizes the route prefix: empty, or `/`-prefixed path segments without a trailing `/`.
+fn parse_api_prefix(value: Option<&str>) -> Result<String> {
+    let prefix = value.unwrap_or("").trim().trim_end_matches('/');
+    if prefix.is_empty() {
+        return Ok(String::new());
+    }
+
+    let valid = prefix.starts_with('/')
+        && prefix[1..].split('/').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '~'))
+        });
+    if !valid {
+        return Err(AppError::Config(format!(
+            "Invalid API_PREFIX '{}': must start with '/' and contain only letters, digits, '-', '_', '.', '~' and '/'",
+            prefix
+        )));
+    }
+
+    Ok(prefix.to_string())
+}
+
+fn parse_env<T: FromStr>(key: &str, default: T) -> Result<T> {
+    Ok(parse_optional_env(key)?.unwrap_or(default))
+}
+
+/// An origin is a scheme and host with an optional port, e.g. `https://ui.example.com:8443`.
+fn validate_origin(origin: &str) -> Result<()> {
+    let host = origin
+        .strip_prefix("https://")
+        .or_else(|| origin.strip_prefix("http://"))
+        .unwrap_or_default();
+    let is_valid = !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']'));
+
+    if !is_valid {
+        return Err(AppError::Config(format!(
+            "Invalid origin '{}' in ALLOWED_ORIGINS: expected a scheme and host such as 'https://ui.example.com', without a path",
+            origin
+        )));
+    }
+    Ok(())
+}
+
+/// Parses an environment variable, treating an unset or blank value as `None`.
+fn parse_optional_env<T: FromStr>(key: &str) -> Result<Option<T>> {
+    match std::env::var(key) {
+        Ok(value) if !value.trim().is_empty() => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| AppError::Config(format!("Invalid value '{}' for {}", value, key))),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_namespace_list() {
+        assert_eq!(parse_namespace_list("ALLOWED_NAMESPACES", None).unwrap(), None);
+        assert_eq!(parse_namespace_list("ALLOWED_NAMESPACES", Some(" , ")).unwrap(), None);
+        assert_eq!(
+            parse_namespace_list("ALLOWED_NAMESPACES", Some("team-a, team-b")).unwrap(),
+            Some(vec!["team-a".to_string(), "team-b".to_string()])
+        );
+        assert!(parse_namespace_list("ALLOWED_NAMESPACES", Some("team-a,Team_B")).is_err());
+    }
+
+    #[test]
+    fn test_metrics_namespaces_fall_back_to_allow_list() {
+        let mut config = Config::default();
+        assert_eq!(config.metrics_namespaces(), None);
+
+        config.allowed_namespaces = Some(vec!["team-a".to_string(), "team-b".to_string()]);
+        assert_eq!(config.metrics_namespaces(), Some(&["team-a".to_string(), "team-b".to_string()][..]));
+
+        config.metrics_namespaces = Some(vec!["team-b".to_string()]);
+        assert_eq!(config.metrics_namespaces(), Some(&["team-b".to_string()][..]));
+    }
+
+    #[test]
+    fn test_validate_origin() {
+        assert!(validate_origin("https://ui.example.com").is_ok());
+        assert!(validate_origin("http://localhost:5173").is_ok());
+        assert!(validate_origin("https://ui.example.com/").is_err());
+        assert!(validate_origin("ui.example.com").is_err());
+        assert!(validate_origin("*").is_err());
+    }
+
+    #[test]
+    fn test_parse_api_prefix() {
+        assert_eq!(parse_api_prefix(None).unwrap(), "");
+        assert_eq!(parse_api_prefix(Some("/")).unwrap(), "");
+        assert_eq!(parse_api_prefix(Some("/api/v1/")).unwrap(), "/api/v1");
+        assert!(parse_api_prefix(Some("api/v1")).is_err());
+        assert!(parse_api_prefix(Some("/api//v1")).is_err());
+        assert!(parse_api_prefix(Some("/:tenant")).is_err());
+    }
+}
@@ -1,11 +1,17 @@
-use axum::{http::StatusCode, response::Json as ResponseJson};
-use serde_json::json;
+use crate::config::{self, ErrorDetail};
+use crate::middleware::current_request_id;
+use crate::utils::timeout;
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::Json as ResponseJson,
+};
+use serde_json::{json, Value};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Kubernetes error: {0}")]
-    Kube(#[from] kube::Error),
+    Kube(kube::Error),
     #[error("Serialization error: {0}")]
     Serde(#[from] serde_yaml::Error),
     #[error("JSON error: {0}")]
@@ -20,21 +26,61 @@ pub enum AppError {
     Validation(String),
     #[error("Configuration error: {0}")]
     Config(String),
-    #[error("Network error: {0}")]
-    Network(String),
     #[error("Timeout error: {0}")]
     Timeout(String),
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    /// The service is up but refuses the request for now, e.g. in read-only mode.
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+    /// The request body is larger than `MAX_BODY_BYTES`.
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+    /// A conflict with structured details (e.g. a diff) returned alongside the message.
+    #[error("Conflict: {0}")]
+    Conflict(String, Value),
 }
 
-impl axum::response::IntoResponse for AppError {
-    fn into_response(self) -> axum::response::Response {
+impl From<kube::Error> for AppError {
+    /// API calls cut off by the request's timeout (see `utils::timeout`) become `Timeout`.
+    fn from(error: kube::Error) -> Self {
+        match error {
+            kube::Error::Service(err) if err.is::<timeout::TimedOut>() => AppError::Timeout(err.to_string()),
+            error => AppError::Kube(error),
+        }
+    }
+}
+
+impl AppError {
+    /// The status code and `{"error": {...}}` body reported for this error. Also used for the per-item
+    /// errors of batch responses.
+    pub fn into_body(self) -> (StatusCode, Value) {
+        let details = match &self {
+            AppError::Conflict(_, details) => Some(details.clone()),
+            _ => None,
+        };
+        let sensitive = self.is_sensitive();
+        
         let (status, error_message, error_type) = match self {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg, "NotFound"),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg, "BadRequest"),
             AppError::Validation(msg) => (StatusCode::BAD_REQUEST, msg, "Validation"),
             AppError::Config(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg, "Configuration"),
-            AppError::Network(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg, "Network"),
             AppError::Timeout(msg) => (StatusCode::REQUEST_TIMEOUT, msg, "Timeout"),
+            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg, "TooManyRequests"),
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg, "Unauthorized"),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg, "Forbidden"),
+            AppError::Unavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg, "Unavailable"),
+            AppError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg, "PayloadTooLarge"),
+            AppError::Conflict(msg, _) => (StatusCode::CONFLICT, msg, "Conflict"),
+            // Errors wrapped explicitly rather than converted with `?`
+            AppError::Kube(kube::Error::Service(err)) if err.is::<timeout::TimedOut>() => {
+                (StatusCode::REQUEST_TIMEOUT, err.to_string(), "Timeout")
+            }
             AppError::Kube(err) => {
                 // Handle specific Kubernetes errors more gracefully
                 let (status, msg) = match &err {
@@ -44,6 +90,7 @@ impl axum::response::IntoResponse for AppError {
                         401 => (StatusCode::UNAUTHORIZED, format!("Unauthorized: {}", api_err.message)),
                         403 => (StatusCode::FORBIDDEN, format!("Forbidden: {}", api_err.message)),
                         409 => (StatusCode::CONFLICT, format!("Conflict: {}", api_err.message)),
+                        410 => (StatusCode::GONE, format!("Expired: {}", api_err.message)),
                         _ => (StatusCode::INTERNAL_SERVER_ERROR, format!("Kubernetes error: {}", api_err.message)),
                     },
                     kube::Error::Auth(auth_err) => (StatusCode::UNAUTHORIZED, format!("Authentication error: {}", auth_err)),
@@ -56,24 +103,143 @@ impl axum::response::IntoResponse for AppError {
             AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg, "Internal"),
         };
         
-        // Log the error for debugging
+        let request_id = current_request_id();
+        
+        // Log the error for debugging, always with the full message
         tracing::error!(
             error_type = error_type,
             error_message = error_message,
             status_code = status.as_u16(),
+            request_id = request_id.as_deref(),
             "Request failed"
         );
         
-        let body = ResponseJson(json!({
+        let config = config::get();
+        let message = public_message(config.error_detail, sensitive, error_message, request_id.as_deref());
+        
+        let mut body = json!({
             "error": {
                 "type": error_type,
-                "message": error_message,
-                "status": status.as_u16()
+                "message": message,
+                "status": status.as_u16(),
+                "request_id": request_id
             }
-        }));
+        });
+        if let Some(details) = details {
+            body["error"]["details"] = details;
+        }
         
-        (status, body).into_response()
+        (status, body)
+    }
+
+    /// Whether the message can expose cluster details: internal errors, failures to reach the API server
+    /// and its 5xx answers. Its 4xx answers describe the caller's own mistake and are safe to return.
+    fn is_sensitive(&self) -> bool {
+        match self {
+            AppError::Internal(_) => true,
+            AppError::Kube(kube::Error::Api(api_err)) => api_err.code >= 500,
+            AppError::Kube(kube::Error::Service(err)) if err.is::<timeout::TimedOut>() => false,
+            AppError::Kube(_) => true,
+            _ => false,
+        }
+    }
+}
+
+impl axum::response::IntoResponse for AppError {
+    fn into_response(self) -> axum::response::Response {
+        let (status, body) = self.into_body();
+        
+        let mut response = (status, ResponseJson(body)).into_response();
+        // Tell clients of overload responses when to try again
+        if matches!(status, StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE) {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(config::get().retry_after.as_secs()));
+        }
+        response
+    }
+}
+
+/// Sensitive messages (see `AppError::is_sensitive`) are only returned verbatim with `ERROR_DETAIL=full`.
+fn public_message(detail: ErrorDetail, sensitive: bool, message: String, request_id: Option<&str>) -> String {
+    if detail == ErrorDetail::Full || !sensitive {
+        return message;
+    }
+
+    match request_id {
+        Some(id) => format!("The request could not be completed (request id: {})", id),
+        None => "The request could not be completed".to_string(),
     }
 }
 
-pub type Result<T> = std::result::Result<T, AppError>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+
+    #[test]
+    fn test_public_message_redacts_internal_errors() {
+        let leaked = "etcd at 10.0.0.3 unreachable".to_string();
+
+        let minimal = public_message(ErrorDetail::Minimal, true, leaked.clone(), Some("abc"));
+        assert!(!minimal.contains("10.0.0.3"));
+        assert!(minimal.contains("abc"));
+
+        assert_eq!(public_message(ErrorDetail::Full, true, leaked.clone(), None), leaked);
+        assert_eq!(public_message(ErrorDetail::Minimal, false, leaked.clone(), None), leaked);
+    }
+
+    #[test]
+    fn test_client_errors_from_the_api_server_keep_their_message() {
+        let api_error = |code: u16, message: &str| {
+            AppError::Kube(kube::Error::Api(kube::error::ErrorResponse {
+                status: "Failure".to_string(),
+                message: message.to_string(),
+                reason: String::new(),
+                code,
+            }))
+        };
+        assert_eq!(config::get().error_detail, ErrorDetail::Minimal);
+
+        let (status, body) = api_error(409, "clusters.postgresql.cnpg.io \"db\" already exists").into_body();
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body["error"]["message"].as_str().unwrap().contains("\"db\" already exists"));
+
+        let strict = "strict decoding error: unknown field \"spec.instancez\"";
+        let (status, body) = api_error(400, strict).into_body();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"]["message"].as_str().unwrap().contains(strict));
+
+        // Server-side failures and unreachable API servers are still redacted
+        let (_, body) = api_error(500, "etcd at 10.0.0.3 unreachable").into_body();
+        assert!(!body["error"]["message"].as_str().unwrap().contains("10.0.0.3"));
+        assert!(AppError::Kube(kube::Error::LinesCodecMaxLineLengthExceeded).is_sensitive());
+    }
+
+    #[test]
+    fn test_timed_out_calls_become_timeouts() {
+        let timed_out = || kube::Error::Service(Box::new(timeout::TimedOut(std::time::Duration::from_secs(30))));
+
+        let error = AppError::from(timed_out());
+        assert!(matches!(error, AppError::Timeout(ref msg) if msg.contains("30s")));
+        assert_eq!(error.into_response().status(), StatusCode::REQUEST_TIMEOUT);
+        assert_eq!(AppError::Kube(timed_out()).into_response().status(), StatusCode::REQUEST_TIMEOUT);
+    }
+
+    #[test]
+    fn test_retry_after_on_overload_responses() {
+        let expected = config::get().retry_after.as_secs().to_string();
+
+        let response = AppError::TooManyRequests("queue full".to_string()).into_response();
+        assert_eq!(response.headers()[header::RETRY_AFTER], expected.as_str());
+
+        let response = AppError::Unavailable("read-only mode".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], expected.as_str());
+
+        let response = AppError::BadRequest("bad".to_string()).into_response();
+        assert!(response.headers().get(header::RETRY_AFTER).is_none());
+    }
+}
\ No newline at end of file
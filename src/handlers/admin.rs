@@ -0,0 +1,20 @@
+use crate::error::Result;
+use crate::models::ReadOnlyRequest;
+use crate::utils::format::Payload;
+use crate::utils::maintenance;
+use axum::response::Json as ResponseJson;
+use serde_json::{json, Value};
+
+/// Turns read-only maintenance mode on or off.
+pub async fn set_read_only(Payload(payload): Payload<ReadOnlyRequest>) -> Result<ResponseJson<Value>> {
+    let previous = maintenance::set_read_only(payload.enabled);
+
+    if previous != payload.enabled {
+        tracing::warn!(read_only = payload.enabled, "Read-only mode changed");
+    }
+
+    Ok(ResponseJson(json!({
+        "read_only": payload.enabled,
+        "previous": previous
+    })))
+}
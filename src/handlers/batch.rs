@@ -0,0 +1,67 @@
+use crate::error::{AppError, Result};
+use crate::models::{BatchDeleteItem, BatchDeleteRequest, DeleteQuery};
+use crate::registry;
+use crate::resources::{self, quarantine};
+use crate::utils::batch::BatchResponse;
+use crate::utils::format::Payload;
+use crate::utils::{concurrency, validation};
+use axum::extract::Query;
+use kube::api::DeleteParams;
+use kube::Client;
+use serde_json::{json, Value};
+
+/// Upper bound on items per batch, so one request cannot hold the mutation slots for too long.
+const MAX_BATCH_ITEMS: usize = 100;
+
+/// Deletes (or with `?soft=true` quarantines) several resources of any enabled type.
+///
+/// Items are processed in order and independently; one failing does not stop the others.
+pub async fn batch_delete(
+    Query(query): Query<DeleteQuery>,
+    Payload(payload): Payload<BatchDeleteRequest>,
+) -> Result<BatchResponse> {
+    if payload.items.is_empty() {
+        return Err(AppError::BadRequest("items must not be empty".to_string()));
+    }
+    if payload.items.len() > MAX_BATCH_ITEMS {
+        return Err(AppError::BadRequest(format!(
+            "A batch can contain at most {} items",
+            MAX_BATCH_ITEMS
+        )));
+    }
+    let params = resources::delete_params(query.propagation_policy.as_deref())?;
+    
+    let client = resources::kube_client().await?;
+    
+    let mut response = BatchResponse::default();
+    for item in &payload.items {
+        response.push(delete_item(client.clone(), item, &params, query.soft).await);
+    }
+    
+    Ok(response)
+}
+
+async fn delete_item(client: Client, item: &BatchDeleteItem, params: &DeleteParams, soft: bool) -> Result<Value> {
+    // Validate input
+    validation::validate_namespace(&item.namespace)?;
+    validation::validate_resource_name(&item.name)?;
+    let module = registry::get().module(&item.resource)?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&item.namespace).await?;
+    
+    if soft {
+        return quarantine::quarantine(client, module, &item.namespace, &item.name).await;
+    }
+    
+    // Same manager delete as the single-resource endpoint, so dependents are cleaned up too
+    module.delete(client, &item.namespace, &item.name, params).await?;
+    
+    tracing::info!(resource = module.name(), name = item.name, namespace = item.namespace, "Resource deleted in batch");
+    Ok(json!({
+        "message": format!("{} '{}' deleted successfully", module.kind(), item.name),
+        "resource": module.name(),
+        "name": item.name,
+        "namespace": item.namespace
+    }))
+}
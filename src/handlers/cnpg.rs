@@ -1,113 +1,720 @@
+use crate::config;
 use crate::error::{AppError, Result};
-use crate::models::cnpg::{CreateClusterRequest, UpdateClusterRequest};
-use crate::models::ListQuery;
+use crate::models::cnpg::{
+    CreateClusterRequest, CreateDatabaseRequest, CreateScheduledBackupRequest, RotateCredentialsQuery,
+    ScaleClusterRequest, TriggerBackupQuery, UpdateClusterRequest, UpgradeClusterRequest,
+};
+use crate::models::cnpg::Cluster;
+use crate::models::{CreateQuery, DeleteQuery, DryRun, ListQuery, UpdateQuery, WatchQuery};
+use crate::registry;
 use crate::resources::cnpg::CnpgManager;
-use crate::resources::ResourceManager;
-use crate::utils::validation;
+use crate::resources::cnpg_database::CnpgDatabaseManager;
+use crate::resources::{self, quarantine, ResourceManager, WriteOptions};
+use crate::utils::field_validation::{self, FieldValidation};
+use crate::utils::format::{self, Payload, ResponseFormat};
+use crate::utils::namespace::HeaderNamespace;
+use crate::utils::{coalesce, concurrency, diff, pagination, validation, warnings, watch};
 use axum::{
-    extract::{Json, Path, Query},
-    response::Json as ResponseJson,
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Json as ResponseJson, Response},
 };
-use kube::Client;
-use serde_json::Value;
+use kube::{runtime::watcher, Api};
+use serde_json::{json, Value};
 
-pub async fn create_cluster(Json(payload): Json<CreateClusterRequest>) -> Result<ResponseJson<Value>> {
-    // Validate input
-    validation::validate_resource_name(&payload.name)?;
-    validation::validate_database_name(&payload.database_name)?;
-    validation::validate_database_name(&payload.database_owner)?;
-    validation::validate_instance_count(payload.instances)?;
-    validation::validate_storage_size(&payload.storage_size)?;
+/// `resource_type` of the operation responses.
+const CLUSTER: &str = "cnpg-cluster";
+const DATABASE: &str = "cnpg-database";
+
+pub async fn create_cluster(
+    Query(query): Query<CreateQuery>,
+    header_namespace: HeaderNamespace,
+    format: ResponseFormat,
+    Payload(mut payload): Payload<CreateClusterRequest>,
+) -> Result<Response> {
+    payload.namespace = header_namespace.resolve(payload.namespace.take(), "body")?;
     
-    if let Some(ref namespace) = payload.namespace {
-        validation::validate_namespace(namespace)?;
+    // Validate input
+    if let Some(error) = create_cluster_errors(&payload).into_iter().next() {
+        return Err(error);
     }
     
+    let field_validation = FieldValidation::from_query(query.field_validation.as_deref())?;
+    let dry_run = query.dry_run()?;
+    
     // Create Kubernetes client with timeout
-    let client = Client::try_default()
-        .await
-        .map_err(|e| AppError::Config(format!("Failed to create Kubernetes client: {}", e)))?;
+    let client = resources::kube_client().await?;
+    
+    let namespace = payload.namespace.clone().unwrap_or_else(|| config::get().default_namespace.clone());
+    let name = payload.name.clone();
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
     
     let manager = CnpgManager;
-    let result = manager.create(client, payload).await?;
+    let options = WriteOptions {
+        dry_run,
+        if_not_exists: query.if_not_exists,
+        ..Default::default()
+    };
+    let create = manager.create(client, payload, &options);
+    let result = field_validation::scope(field_validation, create).await.map_err(resources::dry_run_error)?;
+    
+    if dry_run {
+        return format.render(format::operation(CLUSTER, format::dry_run(&namespace, &name, result)));
+    }
+    
+    tracing::info!(cluster_name = name, namespace = namespace, "CNPG cluster created successfully");
+    
+    format::created(format, "/cnpg/clusters", &namespace, &name, format::operation(CLUSTER, result))
+}
+
+/// Checks whether a cluster create request would succeed without creating anything.
+///
+/// Runs every client-side check and, if they pass, a server-side dry-run create. When the API
+/// server cannot be reached, only the client-side part is reported (`server_valid` stays `null`).
+pub async fn preflight_cluster(
+    header_namespace: HeaderNamespace,
+    Payload(mut payload): Payload<CreateClusterRequest>,
+) -> Result<ResponseJson<Value>> {
+    payload.namespace = header_namespace.resolve(payload.namespace.take(), "body")?;
+    
+    let mut errors: Vec<Value> = create_cluster_errors(&payload).into_iter().map(error_json).collect();
+    let client_valid = errors.is_empty();
+    let mut report = json!({
+        "client_valid": client_valid,
+        "server_valid": null,
+        "resulting_spec": null
+    });
+    
+    if client_valid {
+        let options = WriteOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let result = match resources::kube_client().await {
+            Ok(client) => CnpgManager.create(client, payload, &options).await.map_err(resources::dry_run_error),
+            Err(e) => Err(e),
+        };
+        
+        match result {
+            Ok(created) => {
+                report["server_valid"] = true.into();
+                report["resulting_spec"] = created["spec"].clone();
+            }
+            Err(e) if resources::is_unreachable(&e) => report["server_error"] = error_json(e),
+            Err(e) => {
+                report["server_valid"] = false.into();
+                errors.push(error_json(e));
+            }
+        }
+    }
+    
+    report["errors"] = errors.into();
+    report["warnings"] = warnings::take().into();
+    Ok(ResponseJson(report))
+}
+
+fn error_json(error: AppError) -> Value {
+    let (_, mut body) = error.into_body();
+    body["error"].take()
+}
+
+/// Runs every client-side check of a cluster create request and returns all failures, in order.
+fn create_cluster_errors(payload: &CreateClusterRequest) -> Vec<AppError> {
+    let mut errors = Vec::new();
+    let mut check = |result: Result<()>| {
+        if let Err(e) = result {
+            errors.push(e);
+        }
+    };
+    
+    check(validation::validate_resource_name(&payload.name));
+    check(validation::validate_database_name(&payload.database_name));
+    check(validation::validate_database_owner(&payload.database_owner, config::get().allow_reserved_database_owner));
+    check(validation::validate_storage_size(&payload.storage_size));
+    
+    if let Some(ref storage_class) = payload.storage_class {
+        check(validation::validate_storage_class(storage_class, config::get().allowed_storage_classes.as_deref()));
+    }
+    
+    if let Some(instances) = payload.instances {
+        check(validation::validate_instance_count(instances));
+    }
+    
+    if let Some(ref limits) = payload.ephemeral_volumes_size_limit {
+        if let Some(ref temporary_data) = limits.temporary_data {
+            check(validation::validate_storage_size(temporary_data));
+        }
+        if let Some(ref shm) = limits.shm {
+            check(validation::validate_storage_size(shm));
+        }
+    }
+    
+    check(validate_switchover_timeouts(payload.switchover_delay, payload.smart_shutdown_timeout));
+    check(validate_failover_timeouts(payload.promotion_timeout, payload.failover_delay));
+    
+    if let Some(seconds) = payload.stop_delay {
+        check(validation::validate_duration_seconds("stop_delay", seconds));
+    }
+    
+    if let Some(ref parameters) = payload.postgresql_parameters {
+        let config = config::get();
+        check(validation::validate_postgresql_parameters(
+            parameters,
+            &config.postgres_parameter_denylist,
+            config.allow_unknown_postgres_parameters,
+        ));
+    }
+    
+    if let Some(ref env) = payload.env {
+        check(validation::validate_env_var_names(env));
+    }
+    
+    if let Some(ref pg_ident) = payload.pg_ident {
+        check(validation::validate_pg_ident(pg_ident));
+    }
+    
+    if payload.service_monitor == Some(true) && payload.monitoring_enabled != Some(true) {
+        check(Err(AppError::Validation("service_monitor requires monitoring_enabled".to_string())));
+    }
+    
+    if let Some(ref ldap) = payload.ldap {
+        check(validation::validate_ldap(ldap));
+    }
+    
+    if let Some(ref priority_class_name) = payload.priority_class_name {
+        check(validation::validate_resource_name(priority_class_name));
+    }
+    
+    for constraint in payload.topology_spread_constraints.iter().flatten() {
+        check(validation::validate_topology_spread_constraint(
+            constraint.max_skew,
+            &constraint.topology_key,
+            constraint.when_unsatisfiable.as_deref(),
+        ));
+        if let Some(ref labels) = constraint.match_labels {
+            check(validation::validate_labels(labels));
+        }
+    }
+    
+    if let Some(ref labels) = payload.labels {
+        check(validation::validate_labels(labels));
+    }
+    
+    if let Some(ref annotations) = payload.annotations {
+        check(validation::validate_annotations(annotations));
+    }
+    
+    if let Some(ref namespace) = payload.namespace {
+        check(validation::validate_namespace(namespace));
+    }
+    
+    errors
+}
+
+/// Streams cluster changes in a namespace as Server-Sent Events until the client disconnects.
+pub async fn watch_clusters(Query(query): Query<WatchQuery>, header_namespace: HeaderNamespace) -> Result<Response> {
+    let namespace = header_namespace
+        .resolve(query.namespace, "query")?
+        .unwrap_or_else(|| config::get().default_namespace.clone());
+    validation::validate_namespace(&namespace)?;
     
-    tracing::info!(cluster_name = result.get("metadata").and_then(|m| m.get("name")).and_then(|n| n.as_str()).unwrap_or("unknown"), "CNPG cluster created successfully");
+    let mut config = watcher::Config::default();
+    if let Some(ref selector) = query.label_selector {
+        validation::validate_label_selector(selector)?;
+        config = config.labels(selector);
+    }
+    
+    let client = resources::kube_client().await?;
+    let api: Api<Cluster> = Api::namespaced(client, &namespace);
     
-    Ok(ResponseJson(result))
+    Ok(watch::sse(api, config).into_response())
 }
 
-pub async fn get_cluster(Path((namespace, name)): Path<(String, String)>) -> Result<ResponseJson<Value>> {
+pub async fn get_cluster(
+    Path((namespace, name)): Path<(String, String)>,
+    format: ResponseFormat,
+) -> Result<Response> {
     // Validate input
     validation::validate_namespace(&namespace)?;
     validation::validate_resource_name(&name)?;
     
-    let client = Client::try_default()
-        .await
-        .map_err(|e| AppError::Config(format!("Failed to create Kubernetes client: {}", e)))?;
+    let client = resources::kube_client().await?;
     
-    let manager = CnpgManager;
-    let cluster = manager.get(client, &namespace, &name).await?;
+    // Identical concurrent reads share one API call
+    let key = coalesce::get_key("cnpg", &namespace, &name);
+    let value = coalesce::run(key, async move {
+        let cluster = CnpgManager.get(client, &namespace, &name).await?;
+        serde_json::to_value(cluster).map_err(|e| {
+            AppError::Internal(format!("Failed to serialize cluster: {}", e))
+        })
+    })
+    .await?;
     
-    Ok(ResponseJson(serde_json::to_value(cluster).map_err(|e| {
-        AppError::Internal(format!("Failed to serialize cluster: {}", e))
-    })?))
+    format.render(value)
 }
 
-pub async fn list_clusters(Query(params): Query<ListQuery>) -> Result<ResponseJson<Value>> {
-    let namespace = params.namespace.as_deref().unwrap_or("default");
+pub async fn list_clusters(
+    Query(mut params): Query<ListQuery>,
+    header_namespace: HeaderNamespace,
+    format: ResponseFormat,
+) -> Result<Response> {
+    params.namespace = header_namespace.resolve(params.namespace.take(), "query")?;
     
-    // Validate namespace if provided
-    if params.namespace.is_some() {
-        validation::validate_namespace(namespace)?;
-    }
+    // Resolve namespace and paging options (validates the namespace and cursor)
+    let (namespace, options) = pagination::resolve_list_query(&params)?;
     
-    let client = Client::try_default()
-        .await
-        .map_err(|e| AppError::Config(format!("Failed to create Kubernetes client: {}", e)))?;
+    let client = resources::kube_client().await?;
     
-    let manager = CnpgManager;
-    let result = manager.list(client, namespace).await?;
+    if format == ResponseFormat::Ndjson {
+        return pagination::stream_ndjson(options, move |page| {
+            let (client, namespace) = (client.clone(), namespace.clone());
+            async move { CnpgManager.list(client, &namespace, &page).await }
+        })
+        .await;
+    }
     
-    Ok(ResponseJson(result))
+    let key = coalesce::list_key("cnpg", &namespace, &options);
+    let (list_namespace, list_options) = (namespace.clone(), options.clone());
+    let mut result = coalesce::run(key, async move {
+        CnpgManager.list(client, &list_namespace, &list_options).await
+    })
+    .await?;
+    pagination::attach_cursor(&mut result, &namespace, &options)?;
+    
+    format.render(result)
 }
 
 pub async fn update_cluster(
     Path((namespace, name)): Path<(String, String)>,
-    Json(payload): Json<UpdateClusterRequest>,
-) -> Result<ResponseJson<Value>> {
+    Query(query): Query<UpdateQuery>,
+    format: ResponseFormat,
+    Payload(payload): Payload<UpdateClusterRequest>,
+) -> Result<Response> {
     // Validate input
     validation::validate_namespace(&namespace)?;
     validation::validate_resource_name(&name)?;
+    validation::validate_namespace_matches_path(&namespace, payload.namespace.as_deref())?;
     
     if let Some(instances) = payload.instances {
         validation::validate_instance_count(instances)?;
     }
     
-    let client = Client::try_default()
-        .await
-        .map_err(|e| AppError::Config(format!("Failed to create Kubernetes client: {}", e)))?;
+    validate_switchover_timeouts(payload.switchover_delay, payload.smart_shutdown_timeout)?;
+    validate_failover_timeouts(payload.promotion_timeout, payload.failover_delay)?;
+    
+    if let Some(seconds) = payload.stop_delay {
+        validation::validate_duration_seconds("stop_delay", seconds)?;
+    }
+    
+    if let Some(ref parameters) = payload.postgresql_parameters {
+        let config = config::get();
+        validation::validate_postgresql_parameters(
+            parameters,
+            &config.postgres_parameter_denylist,
+            config.allow_unknown_postgres_parameters,
+        )?;
+    }
+    
+    if let Some(ref pg_ident) = payload.pg_ident {
+        validation::validate_pg_ident(pg_ident)?;
+    }
+    
+    // Cluster updates are merge patches rather than server-side apply
+    if query.force_ownership {
+        return Err(AppError::BadRequest(
+            "force_ownership is only supported for notebook updates".to_string(),
+        ));
+    }
+    
+    let field_validation = FieldValidation::from_query(query.field_validation.as_deref())?;
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
     
     let manager = CnpgManager;
-    let result = manager.update(client, &namespace, &name, payload).await?;
+    
+    let dry_run = query.dry_run()?;
+    if dry_run == DryRun::Object {
+        let options = WriteOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let proposed = field_validation::scope(field_validation, manager.update(client, &namespace, &name, payload, &options))
+            .await
+            .map_err(resources::dry_run_error)?;
+        return format.render(format::operation(CLUSTER, format::dry_run(&namespace, &name, proposed)));
+    }
+    
+    if dry_run == DryRun::Diff {
+        // Compare against the server's dry-run result so defaulting and webhooks are reflected
+        let current = serde_json::to_value(manager.get(client.clone(), &namespace, &name).await?)?;
+        let options = WriteOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let proposed = field_validation::scope(field_validation, manager.update(client, &namespace, &name, payload, &options))
+            .await
+            .map_err(resources::dry_run_error)?;
+        
+        let result = serde_json::json!({
+            "dry_run": true,
+            "name": name,
+            "namespace": namespace,
+            "changes": diff::object_changes(&current, &proposed)
+        });
+        return format.render(format::operation(CLUSTER, result));
+    }
+    
+    let options = WriteOptions::default();
+    let update = manager.update(client, &namespace, &name, payload, &options);
+    let result = field_validation::scope(field_validation, update).await?;
     
     tracing::info!(cluster_name = name, namespace = namespace, "CNPG cluster updated successfully");
     
-    Ok(ResponseJson(result))
+    format.render(format::operation(CLUSTER, result))
 }
 
-pub async fn delete_cluster(Path((namespace, name)): Path<(String, String)>) -> Result<ResponseJson<Value>> {
+pub async fn delete_cluster(
+    Path((namespace, name)): Path<(String, String)>,
+    Query(query): Query<DeleteQuery>,
+) -> Result<ResponseJson<Value>> {
     // Validate input
     validation::validate_namespace(&namespace)?;
     validation::validate_resource_name(&name)?;
+    let params = resources::delete_params(query.propagation_policy.as_deref())?;
     
-    let client = Client::try_default()
-        .await
-        .map_err(|e| AppError::Config(format!("Failed to create Kubernetes client: {}", e)))?;
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    if query.soft {
+        let module = registry::get().module("cnpg")?;
+        let result = quarantine::quarantine(client, module, &namespace, &name).await?;
+        return Ok(ResponseJson(format::operation(CLUSTER, result)));
+    }
     
     let manager = CnpgManager;
-    let result = manager.delete(client, &namespace, &name).await?;
+    let result = manager.delete(client, &namespace, &name, &params).await?;
     
     tracing::info!(cluster_name = name, namespace = namespace, "CNPG cluster deleted successfully");
     
-    Ok(ResponseJson(result))
-}
\ No newline at end of file
+    Ok(ResponseJson(format::operation(CLUSTER, result)))
+}
+
+pub async fn scale_cluster(
+    Path((namespace, name)): Path<(String, String)>,
+    Payload(payload): Payload<ScaleClusterRequest>,
+) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    validation::validate_instance_count(payload.instances)?;
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let manager = CnpgManager;
+    let result = manager.scale(client, &namespace, &name, payload.instances).await?;
+    
+    tracing::info!(cluster_name = name, namespace = namespace, instances = payload.instances, "CNPG cluster scaled");
+    
+    Ok(ResponseJson(format::operation(CLUSTER, result)))
+}
+
+pub async fn upgrade_cluster(
+    Path((namespace, name)): Path<(String, String)>,
+    Payload(payload): Payload<UpgradeClusterRequest>,
+) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    if let Some(ref image_name) = payload.image_name {
+        validation::validate_image_name(image_name, config::get().image_tag_policy)?;
+    }
+    
+    if let Some(ref image_catalog) = payload.image_catalog {
+        validation::validate_resource_name(image_catalog)?;
+        
+        if payload.image_name.is_some() {
+            return Err(AppError::Validation(
+                "Specify either image_name or image_catalog, not both".to_string()
+            ));
+        }
+    }
+    
+    if let Some(ref kind) = payload.image_catalog_kind
+        && kind != "ImageCatalog"
+        && kind != "ClusterImageCatalog"
+    {
+        return Err(AppError::Validation(
+            "image_catalog_kind must be 'ImageCatalog' or 'ClusterImageCatalog'".to_string()
+        ));
+    }
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let manager = CnpgManager;
+    let result = manager.upgrade(client, &namespace, &name, payload).await?;
+    
+    tracing::warn!(cluster_name = name, namespace = namespace, "CNPG cluster major version upgrade requested");
+    
+    Ok(ResponseJson(format::operation(CLUSTER, result)))
+}
+
+pub async fn rotate_credentials(
+    Path((namespace, name)): Path<(String, String)>,
+    Query(query): Query<RotateCredentialsQuery>,
+) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let manager = CnpgManager;
+    let result = manager.rotate_credentials(client, &namespace, &name, query.reveal).await?;
+    
+    Ok(ResponseJson(format::operation(CLUSTER, result)))
+}
+
+pub async fn trigger_backup(
+    Path((namespace, name)): Path<(String, String)>,
+    Query(query): Query<TriggerBackupQuery>,
+) -> Result<(StatusCode, ResponseJson<Value>)> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    if let Some(ref backup_name) = query.name {
+        validation::validate_resource_name(backup_name)?;
+    }
+    validation::validate_backup_options(query.method.as_deref(), query.target.as_deref())?;
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let manager = CnpgManager;
+    let result = manager.trigger_backup(client, &namespace, &name, query).await?;
+    
+    Ok((StatusCode::CREATED, ResponseJson(format::operation(CLUSTER, result))))
+}
+
+pub async fn create_scheduled_backup(
+    Path((namespace, name)): Path<(String, String)>,
+    Payload(payload): Payload<CreateScheduledBackupRequest>,
+) -> Result<(StatusCode, ResponseJson<Value>)> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    if let Some(ref scheduled_name) = payload.name {
+        validation::validate_resource_name(scheduled_name)?;
+    }
+    validation::validate_cron_schedule(&payload.schedule)?;
+    validation::validate_backup_options(payload.method.as_deref(), payload.target.as_deref())?;
+    
+    if let Some(ref owner) = payload.backup_owner_reference
+        && !matches!(owner.as_str(), "none" | "self" | "cluster")
+    {
+        return Err(AppError::Validation(
+            "backup_owner_reference must be 'none', 'self' or 'cluster'".to_string(),
+        ));
+    }
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let manager = CnpgManager;
+    let result = manager.create_scheduled_backup(client, &namespace, &name, payload).await?;
+    
+    Ok((StatusCode::CREATED, ResponseJson(format::operation(CLUSTER, result))))
+}
+
+pub async fn list_scheduled_backups(
+    Path((namespace, name)): Path<(String, String)>,
+    format: ResponseFormat,
+) -> Result<Response> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    let client = resources::kube_client().await?;
+    
+    let manager = CnpgManager;
+    let result = manager.list_scheduled_backups(client, &namespace, &name).await?;
+    
+    format.render(result)
+}
+
+pub async fn diagnose_cluster(Path((namespace, name)): Path<(String, String)>) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    let client = resources::kube_client().await?;
+    
+    let manager = CnpgManager;
+    let report = manager.diagnose(client, &namespace, &name).await?;
+    
+    Ok(ResponseJson(report))
+}
+
+pub async fn cluster_storage(Path((namespace, name)): Path<(String, String)>) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    let client = resources::kube_client().await?;
+    
+    let manager = CnpgManager;
+    let report = manager.storage(client, &namespace, &name).await?;
+    
+    Ok(ResponseJson(report))
+}
+
+fn validate_failover_timeouts(promotion_timeout: Option<i64>, failover_delay: Option<i64>) -> Result<()> {
+    if let Some(seconds) = promotion_timeout {
+        validation::validate_duration_seconds("promotion_timeout", seconds)?;
+        if seconds == 0 {
+            return Err(AppError::Validation("promotion_timeout must be greater than 0".to_string()));
+        }
+    }
+    // 0 is CNPG's default: fail over as soon as the primary is detected as failed
+    if let Some(seconds) = failover_delay {
+        validation::validate_duration_seconds("failover_delay", seconds)?;
+    }
+    Ok(())
+}
+
+fn validate_switchover_timeouts(switchover_delay: Option<i64>, smart_shutdown_timeout: Option<i64>) -> Result<()> {
+    if let Some(seconds) = switchover_delay {
+        validation::validate_duration_seconds("switchover_delay", seconds)?;
+    }
+    if let Some(seconds) = smart_shutdown_timeout {
+        validation::validate_duration_seconds("smart_shutdown_timeout", seconds)?;
+    }
+    Ok(())
+}
+
+pub async fn create_database(
+    header_namespace: HeaderNamespace,
+    format: ResponseFormat,
+    Payload(mut payload): Payload<CreateDatabaseRequest>,
+) -> Result<Response> {
+    payload.namespace = header_namespace.resolve(payload.namespace.take(), "body")?;
+    
+    // Validate input
+    validation::validate_resource_name(&payload.name)?;
+    validation::validate_database_name(payload.database_name.as_deref().unwrap_or(&payload.name))?;
+    validation::validate_database_owner(&payload.owner, config::get().allow_reserved_database_owner)?;
+    validation::validate_resource_name(&payload.cluster)?;
+    
+    if let Some(ref policy) = payload.reclaim_policy
+        && policy != "retain"
+        && policy != "delete"
+    {
+        return Err(AppError::Validation("reclaim_policy must be 'retain' or 'delete'".to_string()));
+    }
+    
+    if let Some(ref namespace) = payload.namespace {
+        validation::validate_namespace(namespace)?;
+    }
+    
+    let client = resources::kube_client().await?;
+    
+    let namespace = payload.namespace.clone().unwrap_or_else(|| config::get().default_namespace.clone());
+    let name = payload.name.clone();
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let result = CnpgDatabaseManager.create(client, payload).await?;
+    
+    tracing::info!(database = name, namespace = namespace, "CNPG database created successfully");
+    
+    format::created(format, "/cnpg/databases", &namespace, &name, format::operation(DATABASE, result))
+}
+
+pub async fn get_database(
+    Path((namespace, name)): Path<(String, String)>,
+    format: ResponseFormat,
+) -> Result<Response> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    let client = resources::kube_client().await?;
+    
+    let key = coalesce::get_key("cnpg-database", &namespace, &name);
+    let value = coalesce::run(key, async move {
+        let database = CnpgDatabaseManager.get(client, &namespace, &name).await?;
+        serde_json::to_value(database).map_err(|e| {
+            AppError::Internal(format!("Failed to serialize database: {}", e))
+        })
+    })
+    .await?;
+    
+    format.render(value)
+}
+
+pub async fn list_databases(
+    Query(mut params): Query<ListQuery>,
+    header_namespace: HeaderNamespace,
+    format: ResponseFormat,
+) -> Result<Response> {
+    params.namespace = header_namespace.resolve(params.namespace.take(), "query")?;
+    
+    // Resolve namespace and paging options (validates the namespace and cursor)
+    let (namespace, options) = pagination::resolve_list_query(&params)?;
+    
+    let client = resources::kube_client().await?;
+    
+    let key = coalesce::list_key("cnpg-database", &namespace, &options);
+    let (list_namespace, list_options) = (namespace.clone(), options.clone());
+    let mut result = coalesce::run(key, async move {
+        CnpgDatabaseManager.list(client, &list_namespace, &list_options).await
+    })
+    .await?;
+    pagination::attach_cursor(&mut result, &namespace, &options)?;
+    
+    format.render(result)
+}
+
+pub async fn delete_database(
+    Path((namespace, name)): Path<(String, String)>,
+    Query(query): Query<DeleteQuery>,
+) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    let params = resources::delete_params(query.propagation_policy.as_deref())?;
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let result = CnpgDatabaseManager.delete(client, &namespace, &name, &params).await?;
+    
+    tracing::info!(database = name, namespace = namespace, "CNPG database deleted successfully");
+    
+    Ok(ResponseJson(format::operation(DATABASE, result)))
+}
@@ -0,0 +1,21 @@
+use crate::{config, registry};
+use axum::response::Json as ResponseJson;
+use serde_json::{json, Value};
+
+/// Lists the resource types enabled on this instance.
+pub async fn list_resource_types() -> ResponseJson<Value> {
+    let resources: Vec<Value> = registry::get()
+        .modules()
+        .iter()
+        .map(|module| {
+            let mut description = module.describe();
+            description["path"] = format!("{}{}", config::get().api_prefix, module.path()).into();
+            description
+        })
+        .collect();
+
+    ResponseJson(json!({
+        "resources": resources,
+        "count": resources.len()
+    }))
+}
@@ -0,0 +1,51 @@
+use crate::models::{BatchDeleteRequest, DeleteQuery, NamespaceQuery};
+use crate::utils::openapi::ApiDoc;
+use crate::{config, registry};
+use axum::response::{Html, Json as ResponseJson};
+use serde_json::Value;
+use std::sync::LazyLock;
+
+/// Built once: the routes and enabled modules do not change while the service runs.
+static SPEC: LazyLock<Value> = LazyLock::new(|| {
+    let mut doc = ApiDoc::new();
+    doc.operation("get", "/namespaces", "List namespaces");
+    doc.operation("get", "/namespaces/:namespace/constraints", "Resource quotas and limit ranges of a namespace");
+    doc.operation("get", "/resources", "List the enabled resource types");
+    doc.operation("get", "/storage-classes", "List storage classes");
+    doc.operation("get", "/quarantined", "List soft-deleted resources").query::<NamespaceQuery>();
+    doc.operation("delete", "/quarantined/:resource/:namespace/:name", "Delete a quarantined resource now");
+    doc.operation("post", "/quarantined/:resource/:namespace/:name/restore", "Restore a quarantined resource");
+    doc.operation("post", "/batch/delete", "Delete several resources")
+        .query::<DeleteQuery>()
+        .body::<BatchDeleteRequest>();
+    registry::get().document(&mut doc);
+
+    doc.into_spec(&config::get().api_prefix)
+});
+
+/// OpenAPI description of the routes served below `API_PREFIX`.
+pub async fn openapi_spec() -> ResponseJson<Value> {
+    ResponseJson(SPEC.clone())
+}
+
+/// Swagger UI for `openapi_spec`; the UI's assets are loaded from a CDN by the browser.
+pub async fn swagger_ui() -> Html<String> {
+    Html(SWAGGER_UI.replace("{spec_url}", &format!("{}/openapi.json", config::get().api_prefix)))
+}
+
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>K8s Resource Manager API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    window.ui = SwaggerUIBundle({ url: "{spec_url}", dom_id: "#swagger-ui" });
+  </script>
+</body>
+</html>
+"##;
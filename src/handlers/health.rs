@@ -1,10 +1,54 @@
-use axum::response::Json as ResponseJson;
+use crate::error::AppError;
+use crate::resources;
+use crate::utils::{maintenance, supervisor};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json as ResponseJson, Response},
+};
 use serde_json::{json, Value};
 
+/// Liveness probe; never touches the Kubernetes API.
 pub async fn health_check() -> ResponseJson<Value> {
     ResponseJson(json!({
         "status": "healthy",
         "service": "k8s-resource-manager",
         "version": "0.1.0"
     }))
-}
\ No newline at end of file
+}
+
+/// Readiness probe: ready while the Kubernetes API server answers `/version` and no critical
+/// background task is down, 503 otherwise. A down non-critical task reports `degraded` but stays ready.
+///
+/// Read-only mode keeps the service ready, since reads are still served.
+pub async fn readiness_check() -> Response {
+    let version = match resources::kube_client().await {
+        Ok(client) => client.apiserver_version().await.map_err(AppError::from),
+        Err(e) => Err(e),
+    };
+    let down = supervisor::down_tasks();
+
+    let mut body = json!({
+        "status": "ready",
+        "read_only": maintenance::is_read_only()
+    });
+    if !down.critical.is_empty() || !down.other.is_empty() {
+        body["status"] = "degraded".into();
+        body["down_tasks"] = json!(down.critical.iter().chain(&down.other).collect::<Vec<_>>());
+    }
+
+    let ready = match version {
+        Ok(_) => down.critical.is_empty(),
+        Err(e) => {
+            let (_, mut error) = e.into_body();
+            body["error"] = error["error"].take();
+            false
+        }
+    };
+
+    if ready {
+        ResponseJson(body).into_response()
+    } else {
+        body["status"] = "not_ready".into();
+        (StatusCode::SERVICE_UNAVAILABLE, ResponseJson(body)).into_response()
+    }
+}
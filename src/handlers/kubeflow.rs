@@ -1,20 +1,53 @@
+use crate::config;
 use crate::error::{AppError, Result};
-use crate::models::kubeflow::{CreateNotebookRequest, UpdateNotebookRequest};
-use crate::models::ListQuery;
-use crate::resources::kubeflow::KubeflowManager;
-use crate::resources::ResourceManager;
-use crate::utils::validation;
+use crate::models::kubeflow::{
+    CreateNotebookRequest, CreateSnapshotQuery, Notebook, ResizeWorkspaceQuery, UpdateNotebookRequest,
+};
+use crate::models::{CreateQuery, DeleteQuery, DryRun, ListQuery, UpdateQuery, WatchQuery};
+use crate::registry;
+use crate::resources::kubeflow::{self, KubeflowManager};
+use crate::resources::{self, quarantine, ResourceManager, WriteOptions};
+use crate::utils::field_validation::{self, FieldValidation};
+use crate::utils::format::{self, Payload, ResponseFormat};
+use crate::utils::namespace::HeaderNamespace;
+use crate::utils::{coalesce, concurrency, diff, pagination, validation, warnings, watch};
 use axum::{
-    extract::{Json, Path, Query},
-    response::Json as ResponseJson,
+    extract::{Path, Query},
+    response::{IntoResponse, Json as ResponseJson, Response},
 };
-use kube::Client;
+use kube::{runtime::watcher, Api};
+use k8s_openapi::chrono::Utc;
 use serde_json::Value;
 
-pub async fn create_notebook(Json(payload): Json<CreateNotebookRequest>) -> Result<ResponseJson<Value>> {
+/// `resource_type` of the operation responses.
+const NOTEBOOK: &str = "kubeflow-notebook";
+
+pub async fn create_notebook(
+    Query(query): Query<CreateQuery>,
+    header_namespace: HeaderNamespace,
+    format: ResponseFormat,
+    Payload(mut payload): Payload<CreateNotebookRequest>,
+) -> Result<Response> {
+    payload.namespace = header_namespace.resolve(payload.namespace.take(), "body")?;
+    
     // Validate input
     validation::validate_resource_name(&payload.name)?;
-    validation::validate_image_name(&payload.image)?;
+    
+    if let Some(ref image) = payload.image {
+        validation::validate_image_name(image, config::get().image_tag_policy)?;
+    }
+    
+    if let Some(ref policy) = payload.image_pull_policy {
+        validation::validate_image_pull_policy(policy)?;
+    }
+    
+    if let Some(ref command) = payload.command {
+        validation::validate_container_command(command)?;
+    }
+    
+    if let Some(ref storage_class) = payload.workspace_storage_class {
+        validation::validate_storage_class(storage_class, config::get().allowed_storage_classes.as_deref())?;
+    }
     
     if let Some(ref namespace) = payload.namespace {
         validation::validate_namespace(namespace)?;
@@ -36,67 +69,191 @@ pub async fn create_notebook(Json(payload): Json<CreateNotebookRequest>) -> Resu
         validation::validate_memory_resource(memory_limit)?;
     }
     
+    if let Some(ref gpu_limit) = payload.gpu_limit {
+        validation::validate_gpu_count(gpu_limit, config::get().max_gpu_per_notebook)?;
+    }
+    
     if let Some(ref workspace_size) = payload.workspace_volume_size {
         validation::validate_storage_size(workspace_size)?;
     }
     
-    let client = Client::try_default()
-        .await
-        .map_err(|e| AppError::Config(format!("Failed to create Kubernetes client: {}", e)))?;
+    if let Some(ref env) = payload.environment_variables {
+        validation::validate_env_var_names(env)?;
+    }
+    
+    if let Some(ref claims) = payload.resource_claims {
+        validation::validate_resource_claim_names(claims.iter().map(|claim| claim.name.as_str()))?;
+    }
+    
+    if let Some(ref node_selector) = payload.node_selector {
+        validation::validate_labels(node_selector)?;
+    }
+    
+    for toleration in payload.tolerations.iter().flatten() {
+        validation::validate_toleration(
+            toleration.key.as_deref(),
+            toleration.operator.as_deref(),
+            toleration.effect.as_deref(),
+        )?;
+    }
+    
+    if let Some(ref labels) = payload.labels {
+        validation::validate_labels(labels)?;
+    }
+    
+    if let Some(ref annotations) = payload.annotations {
+        validation::validate_annotations(annotations)?;
+    }
+    
+    if let Some(ref existing_pvc) = payload.existing_workspace_pvc {
+        validation::validate_resource_name(existing_pvc)?;
+        
+        if payload.workspace_volume_size.is_some() || payload.create_workspace == Some(true) {
+            return Err(AppError::Validation(
+                "existing_workspace_pvc cannot be combined with workspace_volume_size or create_workspace".to_string()
+            ));
+        }
+    }
+    
+    if payload.create_workspace == Some(false) && payload.workspace_volume_size.is_some() {
+        return Err(AppError::Validation(
+            "workspace_volume_size requires a workspace; remove it or set create_workspace to true".to_string()
+        ));
+    }
+    
+    payload.normalize_quantities();
+    
+    let field_validation = FieldValidation::from_query(query.field_validation.as_deref())?;
+    let dry_run = query.dry_run()?;
+    
+    let client = resources::kube_client().await?;
+    
+    let namespace = payload.namespace.clone().unwrap_or_else(|| config::get().default_namespace.clone());
+    let name = payload.name.clone();
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
     
     let manager = KubeflowManager;
-    let result = manager.create(client, payload).await?;
+    let options = WriteOptions {
+        dry_run,
+        if_not_exists: query.if_not_exists,
+        ..Default::default()
+    };
+    let create = manager.create(client, payload, &options);
+    let result = field_validation::scope(field_validation, create).await.map_err(resources::dry_run_error)?;
     
-    tracing::info!(notebook_name = result.get("metadata").and_then(|m| m.get("name")).and_then(|n| n.as_str()).unwrap_or("unknown"), "Kubeflow notebook created successfully");
+    if dry_run {
+        return format.render(format::operation(NOTEBOOK, format::dry_run(&namespace, &name, result)));
+    }
     
-    Ok(ResponseJson(result))
+    tracing::info!(notebook_name = name, namespace = namespace, "Kubeflow notebook created successfully");
+    
+    format::created(format, "/kubeflow/notebooks", &namespace, &name, format::operation(NOTEBOOK, result))
 }
 
-pub async fn get_notebook(Path((namespace, name)): Path<(String, String)>) -> Result<ResponseJson<Value>> {
+/// Streams notebook changes in a namespace as Server-Sent Events until the client disconnects.
+pub async fn watch_notebooks(Query(query): Query<WatchQuery>, header_namespace: HeaderNamespace) -> Result<Response> {
+    let namespace = header_namespace
+        .resolve(query.namespace, "query")?
+        .unwrap_or_else(|| config::get().default_namespace.clone());
+    validation::validate_namespace(&namespace)?;
+    
+    let mut config = watcher::Config::default();
+    if let Some(ref selector) = query.label_selector {
+        validation::validate_label_selector(selector)?;
+        config = config.labels(selector);
+    }
+    
+    let client = resources::kube_client().await?;
+    let api: Api<Notebook> = Api::namespaced(client, &namespace);
+    
+    Ok(watch::sse(api, config).into_response())
+}
+
+pub async fn get_notebook(
+    Path((namespace, name)): Path<(String, String)>,
+    format: ResponseFormat,
+) -> Result<Response> {
     // Validate input
     validation::validate_namespace(&namespace)?;
     validation::validate_resource_name(&name)?;
     
-    let client = Client::try_default()
-        .await
-        .map_err(|e| AppError::Config(format!("Failed to create Kubernetes client: {}", e)))?;
+    let client = resources::kube_client().await?;
     
-    let manager = KubeflowManager;
-    let notebook = manager.get(client, &namespace, &name).await?;
+    // Identical concurrent reads share one API call
+    let key = coalesce::get_key("kubeflow", &namespace, &name);
+    let mut value = coalesce::run(key, async move {
+        let notebook = KubeflowManager.get(client.clone(), &namespace, &name).await?;
+        // Best effort: a notebook that can be read is returned even when its pods cannot be listed
+        let pod_status = KubeflowManager.pod_status(client, &namespace, &name).await;
+        let mut value = serde_json::to_value(notebook).map_err(|e| {
+            AppError::Internal(format!("Failed to serialize notebook: {}", e))
+        })?;
+        if let (Some(object), Value::Object(pod_status)) = (value.as_object_mut(), pod_status) {
+            object.extend(pod_status);
+        }
+        Ok(value)
+    })
+    .await?;
+    kubeflow::set_cull_countdown(&mut value, Utc::now(), config::get().notebook_cull_idle_time);
+    warnings::attach(&mut value);
     
-    Ok(ResponseJson(serde_json::to_value(notebook).map_err(|e| {
-        AppError::Internal(format!("Failed to serialize notebook: {}", e))
-    })?))
+    format.render(value)
 }
 
-pub async fn list_notebooks(Query(params): Query<ListQuery>) -> Result<ResponseJson<Value>> {
-    let namespace = params.namespace.as_deref().unwrap_or("default");
+pub async fn list_notebooks(
+    Query(mut params): Query<ListQuery>,
+    header_namespace: HeaderNamespace,
+    format: ResponseFormat,
+) -> Result<Response> {
+    params.namespace = header_namespace.resolve(params.namespace.take(), "query")?;
     
-    // Validate namespace if provided
-    if params.namespace.is_some() {
-        validation::validate_namespace(namespace)?;
-    }
+    // Resolve namespace and paging options (validates the namespace and cursor)
+    let (namespace, options) = pagination::resolve_list_query(&params)?;
     
-    let client = Client::try_default()
-        .await
-        .map_err(|e| AppError::Config(format!("Failed to create Kubernetes client: {}", e)))?;
+    let client = resources::kube_client().await?;
     
-    let manager = KubeflowManager;
-    let result = manager.list(client, namespace).await?;
+    if format == ResponseFormat::Ndjson {
+        return pagination::stream_ndjson(options, move |page| {
+            let (client, namespace) = (client.clone(), namespace.clone());
+            async move { KubeflowManager.list(client, &namespace, &page).await }
+        })
+        .await;
+    }
     
-    Ok(ResponseJson(result))
+    let key = coalesce::list_key("kubeflow", &namespace, &options);
+    let (list_namespace, list_options) = (namespace.clone(), options.clone());
+    let mut result = coalesce::run(key, async move {
+        KubeflowManager.list(client, &list_namespace, &list_options).await
+    })
+    .await?;
+    pagination::attach_cursor(&mut result, &namespace, &options)?;
+    
+    format.render(result)
 }
 
 pub async fn update_notebook(
     Path((namespace, name)): Path<(String, String)>,
-    Json(payload): Json<UpdateNotebookRequest>,
-) -> Result<ResponseJson<Value>> {
+    Query(query): Query<UpdateQuery>,
+    format: ResponseFormat,
+    Payload(mut payload): Payload<UpdateNotebookRequest>,
+) -> Result<Response> {
     // Validate input
     validation::validate_namespace(&namespace)?;
     validation::validate_resource_name(&name)?;
+    validation::validate_namespace_matches_path(&namespace, payload.namespace.as_deref())?;
     
     if let Some(ref image) = payload.image {
-        validation::validate_image_name(image)?;
+        validation::validate_image_name(image, config::get().image_tag_policy)?;
+    }
+    
+    if let Some(Some(ref policy)) = payload.image_pull_policy {
+        validation::validate_image_pull_policy(policy)?;
+    }
+    
+    if let Some(ref command) = payload.command {
+        validation::validate_container_command(command)?;
     }
     
     if let Some(ref cpu_request) = payload.cpu_request {
@@ -115,31 +272,236 @@ pub async fn update_notebook(
         validation::validate_memory_resource(memory_limit)?;
     }
     
-    let client = Client::try_default()
-        .await
-        .map_err(|e| AppError::Config(format!("Failed to create Kubernetes client: {}", e)))?;
+    if let Some(ref gpu_limit) = payload.gpu_limit {
+        validation::validate_gpu_count(gpu_limit, config::get().max_gpu_per_notebook)?;
+    }
+    
+    if let Some(ref env) = payload.environment_variables {
+        validation::validate_env_var_names(env)?;
+    }
+    
+    payload.normalize_quantities();
+    
+    let field_validation = FieldValidation::from_query(query.field_validation.as_deref())?;
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
     
     let manager = KubeflowManager;
-    let result = manager.update(client, &namespace, &name, payload).await?;
+    
+    let dry_run = query.dry_run()?;
+    if dry_run == DryRun::Object {
+        let options = WriteOptions {
+            dry_run: true,
+            force_ownership: query.force_ownership,
+            ..Default::default()
+        };
+        let proposed = field_validation::scope(field_validation, manager.update(client, &namespace, &name, payload, &options))
+            .await
+            .map_err(resources::dry_run_error)?;
+        return format.render(format::operation(NOTEBOOK, format::dry_run(&namespace, &name, proposed)));
+    }
+    
+    if dry_run == DryRun::Diff {
+        // Compare against the server's dry-run result so defaulting and webhooks are reflected
+        let current = serde_json::to_value(manager.get(client.clone(), &namespace, &name).await?)?;
+        let options = WriteOptions {
+            dry_run: true,
+            force_ownership: query.force_ownership,
+            ..Default::default()
+        };
+        let proposed = field_validation::scope(field_validation, manager.update(client, &namespace, &name, payload, &options))
+            .await
+            .map_err(resources::dry_run_error)?;
+        
+        let result = serde_json::json!({
+            "dry_run": true,
+            "name": name,
+            "namespace": namespace,
+            "changes": diff::object_changes(&current, &proposed)
+        });
+        return format.render(format::operation(NOTEBOOK, result));
+    }
+    
+    let options = WriteOptions {
+        force_ownership: query.force_ownership,
+        ..Default::default()
+    };
+    let update = manager.update(client, &namespace, &name, payload, &options);
+    let result = field_validation::scope(field_validation, update).await?;
     
     tracing::info!(notebook_name = name, namespace = namespace, "Kubeflow notebook updated successfully");
     
-    Ok(ResponseJson(result))
+    format.render(format::operation(NOTEBOOK, result))
 }
 
-pub async fn delete_notebook(Path((namespace, name)): Path<(String, String)>) -> Result<ResponseJson<Value>> {
+pub async fn delete_notebook(
+    Path((namespace, name)): Path<(String, String)>,
+    Query(query): Query<DeleteQuery>,
+) -> Result<ResponseJson<Value>> {
     // Validate input
     validation::validate_namespace(&namespace)?;
     validation::validate_resource_name(&name)?;
+    let params = resources::delete_params(query.propagation_policy.as_deref())?;
+    
+    let client = resources::kube_client().await?;
     
-    let client = Client::try_default()
-        .await
-        .map_err(|e| AppError::Config(format!("Failed to create Kubernetes client: {}", e)))?;
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    if query.soft {
+        let module = registry::get().module("kubeflow")?;
+        let result = quarantine::quarantine(client, module, &namespace, &name).await?;
+        return Ok(ResponseJson(format::operation(NOTEBOOK, result)));
+    }
     
     let manager = KubeflowManager;
-    let result = manager.delete(client, &namespace, &name).await?;
+    let result = manager.delete(client, &namespace, &name, &params).await?;
     
     tracing::info!(notebook_name = name, namespace = namespace, "Kubeflow notebook deleted successfully");
     
+    Ok(ResponseJson(format::operation(NOTEBOOK, result)))
+}
+
+pub async fn create_workspace_snapshot(
+    Path((namespace, name)): Path<(String, String)>,
+    Query(query): Query<CreateSnapshotQuery>,
+) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    if let Some(ref snapshot_name) = query.name {
+        validation::validate_resource_name(snapshot_name)?;
+    }
+    if let Some(ref snapshot_class) = query.snapshot_class {
+        validation::validate_resource_name(snapshot_class)?;
+    }
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let manager = KubeflowManager;
+    let result = manager
+        .create_workspace_snapshot(client, &namespace, &name, query.name, query.snapshot_class)
+        .await?;
+    
+    Ok(ResponseJson(format::operation(NOTEBOOK, result)))
+}
+
+pub async fn list_workspace_snapshots(Path((namespace, name)): Path<(String, String)>) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    let client = resources::kube_client().await?;
+    
+    let manager = KubeflowManager;
+    let result = manager.list_workspace_snapshots(client, &namespace, &name).await?;
+    
     Ok(ResponseJson(result))
-}
\ No newline at end of file
+}
+
+pub async fn restart_notebook(Path((namespace, name)): Path<(String, String)>) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let manager = KubeflowManager;
+    let result = manager.restart(client, &namespace, &name).await?;
+    
+    Ok(ResponseJson(format::operation(NOTEBOOK, result)))
+}
+
+pub async fn stop_notebook(Path((namespace, name)): Path<(String, String)>) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let manager = KubeflowManager;
+    let result = manager.stop(client, &namespace, &name).await?;
+    
+    Ok(ResponseJson(format::operation(NOTEBOOK, result)))
+}
+
+pub async fn start_notebook(Path((namespace, name)): Path<(String, String)>) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let manager = KubeflowManager;
+    let result = manager.start(client, &namespace, &name).await?;
+    
+    Ok(ResponseJson(format::operation(NOTEBOOK, result)))
+}
+
+pub async fn resize_workspace(
+    Path((namespace, name)): Path<(String, String)>,
+    Query(query): Query<ResizeWorkspaceQuery>,
+) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    validation::validate_storage_size(&query.size)?;
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let manager = KubeflowManager;
+    let result = manager.resize_workspace(client, &namespace, &name, &query.size).await?;
+    
+    Ok(ResponseJson(format::operation(NOTEBOOK, result)))
+}
+
+pub async fn create_debug_pod(Path((namespace, name)): Path<(String, String)>) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let manager = KubeflowManager;
+    let result = manager.create_debug_pod(client, &namespace, &name).await?;
+    
+    Ok(ResponseJson(format::operation(NOTEBOOK, result)))
+}
+
+pub async fn delete_debug_pod(Path((namespace, name)): Path<(String, String)>) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    
+    let client = resources::kube_client().await?;
+    
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+    
+    let manager = KubeflowManager;
+    let result = manager.delete_debug_pod(client, &namespace, &name).await?;
+    
+    Ok(ResponseJson(format::operation(NOTEBOOK, result)))
+}
@@ -0,0 +1,20 @@
+use crate::error::{AppError, Result};
+use crate::metrics::REGISTRY;
+use axum::{
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use prometheus::{Encoder, TextEncoder};
+
+pub async fn metrics() -> Result<Response> {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    encoder
+        .encode(&REGISTRY.gather(), &mut buffer)
+        .map_err(|e| AppError::Internal(format!("Failed to encode metrics: {}", e)))?;
+
+    let content_type = HeaderValue::from_str(encoder.format_type())
+        .map_err(|e| AppError::Internal(format!("Invalid metrics content type: {}", e)))?;
+
+    Ok(([(header::CONTENT_TYPE, content_type)], buffer).into_response())
+}
@@ -1,3 +1,11 @@
+pub mod admin;
+pub mod batch;
 pub mod cnpg;
+pub mod discovery;
+pub mod docs;
 pub mod health;
-pub mod kubeflow;
\ No newline at end of file
+pub mod kubeflow;
+pub mod metrics;
+pub mod namespaces;
+pub mod quarantine;
+pub mod storage_classes;
\ No newline at end of file
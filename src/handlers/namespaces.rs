@@ -0,0 +1,130 @@
+use crate::config;
+use crate::error::Result;
+use crate::registry;
+use crate::resources;
+use crate::utils::validation;
+use axum::{extract::Path, response::Json as ResponseJson};
+use k8s_openapi::api::core::v1::{LimitRange, Namespace, ResourceQuota};
+use kube::{
+    api::{Api, ListParams},
+    Client, ResourceExt,
+};
+use serde_json::{json, Value};
+
+pub async fn list_namespaces() -> Result<ResponseJson<Value>> {
+    let client = resources::kube_client().await?;
+
+    let allowed = config::get().allowed_namespaces.as_deref();
+
+    let api: Api<Namespace> = Api::all(client.clone());
+    let (names, source) = match api.list(&ListParams::default()).await {
+        Ok(namespaces) => {
+            let names = namespaces
+                .items
+                .iter()
+                .map(|namespace| namespace.name_any())
+                .filter(|name| allowed.is_none_or(|allowed| allowed.contains(name)))
+                .collect::<Vec<_>>();
+            (names, "cluster")
+        }
+        // Without permission to list namespaces, the allow-list is all we know about
+        Err(kube::Error::Api(response)) if response.code == 403 => {
+            tracing::warn!("Not permitted to list namespaces, falling back to ALLOWED_NAMESPACES");
+            (allowed.map(<[String]>::to_vec).unwrap_or_default(), "allow-list")
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    // CRDs are cluster-scoped, so availability is the same for every namespace
+    let crds = crd_availability(&client).await;
+
+    let items: Vec<Value> = names
+        .iter()
+        .map(|name| json!({ "name": name, "crds": crds }))
+        .collect();
+
+    Ok(ResponseJson(json!({
+        "items": items,
+        "count": items.len(),
+        "source": source
+    })))
+}
+
+async fn crd_availability(client: &Client) -> Value {
+    let mut crds = serde_json::Map::new();
+
+    for module in registry::get().modules() {
+        let (api_version, plural) = (module.api_version(), module.plural());
+        let available = match client.list_api_group_resources(&api_version).await {
+            Ok(resources) => resources.resources.iter().any(|resource| resource.name == plural),
+            Err(e) => {
+                tracing::debug!(api_version = %api_version, error = %e, "API group not available");
+                false
+            }
+        };
+        crds.insert(module.name().to_string(), Value::Bool(available));
+    }
+
+    Value::Object(crds)
+}
+
+/// Resource quotas and limit ranges of a namespace, so clients can check requests before creating anything.
+///
+/// A namespace without either returns empty lists.
+pub async fn namespace_constraints(Path(namespace): Path<String>) -> Result<ResponseJson<Value>> {
+    validation::validate_namespace(&namespace)?;
+
+    let client = resources::kube_client().await?;
+
+    let quotas: Api<ResourceQuota> = Api::namespaced(client.clone(), &namespace);
+    let limit_ranges: Api<LimitRange> = Api::namespaced(client, &namespace);
+    let params = ListParams::default();
+    let (quotas, limit_ranges) = tokio::try_join!(
+        quotas.list(&params),
+        limit_ranges.list(&params),
+    )?;
+
+    let quotas: Vec<Value> = quotas
+        .items
+        .iter()
+        .map(|quota| {
+            let spec = quota.spec.clone().unwrap_or_default();
+            let status = quota.status.clone().unwrap_or_default();
+            json!({
+                "name": quota.name_any(),
+                "hard": spec.hard.unwrap_or_default(),
+                "used": status.used.unwrap_or_default(),
+                "scopes": spec.scopes.unwrap_or_default()
+            })
+        })
+        .collect();
+
+    let limit_ranges: Vec<Value> = limit_ranges
+        .items
+        .iter()
+        .map(|range| {
+            let limits: Vec<Value> = range
+                .spec
+                .iter()
+                .flat_map(|spec| spec.limits.iter())
+                .map(|limit| {
+                    json!({
+                        "type": limit.type_,
+                        "default": limit.default,
+                        "default_request": limit.default_request,
+                        "min": limit.min,
+                        "max": limit.max,
+                        "max_limit_request_ratio": limit.max_limit_request_ratio
+                    })
+                })
+                .collect();
+            json!({ "name": range.name_any(), "limits": limits })
+        })
+        .collect();
+
+    Ok(ResponseJson(json!({
+        "namespace": namespace,
+        "resource_quotas": quotas,
+        "limit_ranges": limit_ranges
+    })))
+}
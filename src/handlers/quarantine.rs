@@ -0,0 +1,59 @@
+use crate::config;
+use crate::error::Result;
+use crate::models::NamespaceQuery;
+use crate::registry;
+use crate::resources::{self, quarantine};
+use crate::utils::namespace::HeaderNamespace;
+use crate::utils::{concurrency, format, validation};
+use axum::{
+    extract::{Path, Query},
+    response::Json as ResponseJson,
+};
+use serde_json::Value;
+
+/// Lists soft-deleted resources of every enabled type in a namespace.
+pub async fn list_quarantined(
+    Query(query): Query<NamespaceQuery>,
+    header_namespace: HeaderNamespace,
+) -> Result<ResponseJson<Value>> {
+    let namespace = header_namespace
+        .resolve(query.namespace, "query")?
+        .unwrap_or_else(|| config::get().default_namespace.clone());
+    validation::validate_namespace(&namespace)?;
+
+    let client = resources::kube_client().await?;
+    let result = quarantine::list(client, registry::get(), &namespace).await?;
+
+    Ok(ResponseJson(result))
+}
+
+pub async fn restore(Path((resource, namespace, name)): Path<(String, String, String)>) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    let module = registry::get().module(&resource)?;
+
+    let client = resources::kube_client().await?;
+
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+
+    let result = quarantine::restore(client, module, &namespace, &name).await?;
+    Ok(ResponseJson(format::operation(&module.resource_type(), result)))
+}
+
+/// Deletes a quarantined resource now instead of after the grace period.
+pub async fn purge(Path((resource, namespace, name)): Path<(String, String, String)>) -> Result<ResponseJson<Value>> {
+    // Validate input
+    validation::validate_namespace(&namespace)?;
+    validation::validate_resource_name(&name)?;
+    let module = registry::get().module(&resource)?;
+
+    let client = resources::kube_client().await?;
+
+    // Bound concurrent writes per namespace; fails with 429 when the queue is full
+    let _permit = concurrency::acquire_mutation_slot(&namespace).await?;
+
+    let result = quarantine::purge(client, module, &namespace, &name).await?;
+    Ok(ResponseJson(format::operation(&module.resource_type(), result)))
+}
@@ -0,0 +1,56 @@
+use crate::config;
+use crate::error::Result;
+use crate::resources;
+use axum::response::Json as ResponseJson;
+use k8s_openapi::api::storage::v1::StorageClass;
+use kube::{
+    api::{Api, ListParams},
+    ResourceExt,
+};
+use serde_json::{json, Value};
+
+const DEFAULT_CLASS_ANNOTATION: &str = "storageclass.kubernetes.io/is-default-class";
+
+/// Storage classes clients may request, intersected with `ALLOWED_STORAGE_CLASSES` when set.
+pub async fn list_storage_classes() -> Result<ResponseJson<Value>> {
+    let client = resources::kube_client().await?;
+
+    let allowed = config::get().allowed_storage_classes.as_deref();
+
+    let api: Api<StorageClass> = Api::all(client);
+    let (items, source) = match api.list(&ListParams::default()).await {
+        Ok(classes) => {
+            let items = classes
+                .items
+                .iter()
+                .filter(|class| allowed.is_none_or(|allowed| allowed.contains(&class.name_any())))
+                .map(|class| {
+                    json!({
+                        "name": class.name_any(),
+                        "provisioner": class.provisioner,
+                        "default": class.annotations().get(DEFAULT_CLASS_ANNOTATION).map(String::as_str) == Some("true")
+                    })
+                })
+                .collect::<Vec<_>>();
+            (items, "cluster")
+        }
+        // Without permission to list storage classes, the allow-list is all we know about
+        Err(kube::Error::Api(response)) if response.code == 403 => {
+            tracing::warn!("Not permitted to list storage classes, falling back to ALLOWED_STORAGE_CLASSES");
+            let items = allowed
+                .unwrap_or_default()
+                .iter()
+                .map(|name| json!({ "name": name }))
+                .collect();
+            (items, "allow-list")
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(ResponseJson(json!({
+        "items": items,
+        "count": items.len(),
+        "restricted": allowed.is_some(),
+        "source": source
+    })))
+}
@@ -1,20 +1,29 @@
+mod config;
 mod error;
 mod handlers;
+mod metrics;
+mod middleware;
 mod models;
+mod registry;
 mod resources;
 mod utils;
 
 use axum::{
-    routing::{delete, get, post, put},
+    routing::{delete, get, post},
     Router,
 };
-use handlers::{cnpg, health, kubeflow};
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
-use tracing_subscriber;
+use config::Config;
+use handlers::{admin, batch, discovery, docs, health, metrics as metrics_handler, namespaces, quarantine, storage_classes};
+use registry::Registry;
+use socket2::{SockRef, TcpKeepalive};
+use tower_http::{limit::RequestBodyLimitLayer, trace::TraceLayer};
+use tokio::net::{TcpListener, TcpSocket};
+use std::future::IntoFuture;
+use std::time::Duration;
 use tokio::signal;
+use tokio::sync::oneshot;
 
-#[tokio::main]
-async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing with error handling
     tracing_subscriber::fmt()
         .with_env_filter(
@@ -23,70 +32,181 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         )
         .try_init()
         .map_err(|e| format!("Failed to initialize tracing: {}", e))?;
-    
+
+    // Load configuration up front so invalid settings fail fast
+    let config = config::init().map_err(|e| format!("Invalid configuration: {}", e))?;
+    let registry = registry::init(config.enabled_resources.as_deref())
+        .map_err(|e| format!("Invalid configuration: {}", e))?;
+
+    // Build the runtime by hand so the worker count can come from the configuration
+    let mut runtime = tokio::runtime::Builder::new_multi_thread();
+    if let Some(worker_threads) = config.worker_threads {
+        runtime.worker_threads(worker_threads);
+    }
+    let runtime = runtime
+        .enable_all()
+        .build()
+        .map_err(|e| format!("Failed to start the Tokio runtime: {}", e))?;
+
+    runtime.block_on(serve(config, registry))
+}
+
+async fn serve(config: &'static Config, registry: &'static Registry) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    // One client for all requests; without a usable Kubernetes configuration there is nothing to serve
+    resources::kube_client().await.map_err(|e| format!("Kubernetes client unavailable: {}", e))?;
+
+    let api = Router::new()
+        .route("/namespaces", get(namespaces::list_namespaces))
+        .route("/namespaces/:namespace/constraints", get(namespaces::namespace_constraints))
+        .route("/resources", get(discovery::list_resource_types))
+        .route("/storage-classes", get(storage_classes::list_storage_classes))
+        .route("/quarantined", get(quarantine::list_quarantined))
+        .route("/quarantined/:resource/:namespace/:name", delete(quarantine::purge))
+        .route("/quarantined/:resource/:namespace/:name/restore", post(quarantine::restore))
+        .route("/batch/delete", post(batch::batch_delete))
+        .route("/openapi.json", get(docs::openapi_spec))
+        .route("/docs", get(docs::swagger_ui))
+        // Routes of the enabled resource types (see registry.rs)
+        .merge(registry.router())
+        .route_layer(axum::middleware::from_fn(middleware::reject_mutations_when_read_only));
+
+    let admin = Router::new()
+        .route("/admin/readonly", post(admin::set_read_only))
+        .route_layer(axum::middleware::from_fn(middleware::require_admin_token));
+
+    // Probes, metrics and admin stay at the root so they keep working behind a prefixing gateway
     let app = Router::new()
-        // Health check
         .route("/health", get(health::health_check))
-        
-        // CNPG routes
-        .route("/cnpg/clusters", post(cnpg::create_cluster))
-        .route("/cnpg/clusters", get(cnpg::list_clusters))
-        .route("/cnpg/clusters/:namespace/:name", get(cnpg::get_cluster))
-        .route("/cnpg/clusters/:namespace/:name", put(cnpg::update_cluster))
-        .route("/cnpg/clusters/:namespace/:name", delete(cnpg::delete_cluster))
-        
-        // Legacy routes for backward compatibility (will be deprecated)
-        .route("/clusters", post(cnpg::create_cluster))
-        .route("/clusters", get(cnpg::list_clusters))
-        .route("/clusters/:namespace/:name", get(cnpg::get_cluster))
-        .route("/clusters/:namespace/:name", put(cnpg::update_cluster))
-        .route("/clusters/:namespace/:name", delete(cnpg::delete_cluster))
-        
-        // Kubeflow routes
-        .route("/kubeflow/notebooks", post(kubeflow::create_notebook))
-        .route("/kubeflow/notebooks", get(kubeflow::list_notebooks))
-        .route("/kubeflow/notebooks/:namespace/:name", get(kubeflow::get_notebook))
-        .route("/kubeflow/notebooks/:namespace/:name", put(kubeflow::update_notebook))
-        .route("/kubeflow/notebooks/:namespace/:name", delete(kubeflow::delete_notebook))
-        
-        // Future routes will be added here:
-        // .route("/kubevirt/vms", post(kubevirt::create_vm))
-        // .route("/strimzi/kafka", post(strimzi::create_kafka))
-        // .route("/cluster-api/clusters", post(capi::create_cluster))
-        
-        .layer(CorsLayer::permissive())
+        .route("/ready", get(health::readiness_check))
+        .route("/metrics", get(metrics_handler::metrics))
+        .merge(admin);
+    let app = if config.api_prefix.is_empty() {
+        app.merge(api)
+    } else {
+        app.nest(&config.api_prefix, api)
+    };
+
+    let app = app
+        .layer(axum::middleware::from_fn(middleware::require_json_content_type))
+        .layer(axum::middleware::from_fn(middleware::apply_timeout_override))
+        .layer(axum::middleware::from_fn(middleware::collect_kube_warnings))
+        .layer(RequestBodyLimitLayer::new(config.max_body_bytes))
+        .layer(axum::middleware::from_fn(middleware::reject_oversized_bodies))
+        .layer(axum::middleware::from_fn(middleware::assign_request_id))
+        .layer(middleware::cors_layer(config))
+        .layer(axum::middleware::from_fn(middleware::track_in_flight))
         .layer(TraceLayer::new_for_http());
-    
+
+    resources::quarantine::spawn_reaper(registry);
+
+    match config.metrics_namespaces() {
+        Some(namespaces) => {
+            metrics::spawn_resource_counter(registry, namespaces.to_vec(), config.resource_count_interval)
+        }
+        None => tracing::info!("Per-namespace resource counts disabled; set METRICS_NAMESPACES or ALLOWED_NAMESPACES"),
+    }
+
     // Bind to the specified address with proper error handling
     let bind_addr = std::env::var("BIND_ADDRESS").unwrap_or_else(|_| "0.0.0.0:3000".to_string());
-    let listener = tokio::net::TcpListener::bind(&bind_addr)
+    let listener = bind_listener(&bind_addr, config)
         .await
         .map_err(|e| format!("Failed to bind to address '{}': {}", bind_addr, e))?;
-    
+
     // Get the actual listening address safely
     let local_addr = listener
         .local_addr()
         .map_err(|e| format!("Failed to get local address: {}", e))?;
-    
+
     tracing::info!("K8s Resource Manager listening on {}", local_addr);
+    tracing::info!(
+        worker_threads = config.worker_threads.or_else(|| std::thread::available_parallelism().ok().map(usize::from)),
+        tcp_backlog = config.tcp_backlog,
+        tcp_keepalive_seconds = config.tcp_keepalive.map(|idle| idle.as_secs()),
+        "Server tuning"
+    );
     tracing::info!("API endpoints:");
     tracing::info!("  Health: GET /health");
-    tracing::info!("  CNPG Clusters: /cnpg/clusters");
-    tracing::info!("  Kubeflow Notebooks: /kubeflow/notebooks");
-    tracing::info!("  Legacy CNPG: /clusters (deprecated)");
-    
+    tracing::info!("  Readiness: GET /ready");
+    tracing::info!("  Metrics: GET /metrics");
+    if config.admin_token.is_some() {
+        tracing::info!("  Read-only mode: POST /admin/readonly");
+    }
+    if config.read_only {
+        tracing::warn!("Starting in read-only mode");
+    }
+    if config.cors_permissive {
+        tracing::warn!("CORS_PERMISSIVE is set: cross-origin requests are allowed from any origin");
+    }
+    tracing::info!("  Namespaces: GET {}/namespaces", config.api_prefix);
+    tracing::info!("  Namespace constraints: GET {}/namespaces/:namespace/constraints", config.api_prefix);
+    tracing::info!("  Resource types: GET {}/resources", config.api_prefix);
+    tracing::info!("  Storage classes: GET {}/storage-classes", config.api_prefix);
+    tracing::info!("  Quarantined resources: GET {}/quarantined", config.api_prefix);
+    tracing::info!("  Batch delete: POST {}/batch/delete", config.api_prefix);
+    tracing::info!("  OpenAPI spec: GET {}/openapi.json", config.api_prefix);
+    tracing::info!("  API docs: GET {}/docs", config.api_prefix);
+    for module in registry.modules() {
+        tracing::info!("  {} {}: {}{}", module.name(), module.kind(), config.api_prefix, module.path());
+    }
+
     // Start the server with graceful shutdown
     tracing::info!("Starting server...");
-    
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .map_err(|e| format!("Server error: {}", e))?;
-    
-    tracing::info!("Server shutdown complete");
+
+    // After the signal no new connections are accepted; in-flight requests get the grace period to finish
+    let (signalled_tx, signalled_rx) = oneshot::channel();
+    let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+        shutdown_signal().await;
+        let _ = signalled_tx.send(());
+    });
+
+    tokio::select! {
+        result = server.into_future() => {
+            result.map_err(|e| format!("Server error: {}", e))?;
+            tracing::info!("Server shutdown complete");
+        }
+        _ = grace_period_elapsed(signalled_rx, config.shutdown_grace_period) => {
+            tracing::warn!(
+                in_flight = metrics::IN_FLIGHT_REQUESTS.get(),
+                grace_seconds = config.shutdown_grace_period.as_secs(),
+                "Shutdown grace period elapsed, exiting with requests still in flight"
+            );
+        }
+    }
     Ok(())
 }
 
+/// Resolves `grace` after the shutdown signal; never resolves if no signal arrives.
+async fn grace_period_elapsed(signalled: oneshot::Receiver<()>, grace: Duration) {
+    if signalled.await.is_err() {
+        return std::future::pending().await;
+    }
+    tracing::info!(
+        in_flight = metrics::IN_FLIGHT_REQUESTS.get(),
+        grace_seconds = grace.as_secs(),
+        "Draining in-flight requests"
+    );
+    tokio::time::sleep(grace).await;
+}
+
+/// Binds the listening socket with the configured backlog and TCP keepalive.
+async fn bind_listener(bind_addr: &str, config: &Config) -> std::io::Result<TcpListener> {
+    let addr = tokio::net::lookup_host(bind_addr).await?.next().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::AddrNotAvailable, "address did not resolve")
+    })?;
+
+    let socket = if addr.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+    #[cfg(unix)]
+    socket.set_reuseaddr(true)?;
+
+    if let Some(idle) = config.tcp_keepalive {
+        // Accepted connections inherit the listening socket's keepalive settings
+        SockRef::from(&socket).set_tcp_keepalive(&TcpKeepalive::new().with_time(idle))?;
+    }
+
+    socket.bind(addr)?;
+    socket.listen(config.tcp_backlog)
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         match signal::ctrl_c().await {
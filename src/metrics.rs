@@ -0,0 +1,112 @@
+use crate::registry::Registry;
+use crate::resources;
+use crate::utils::supervisor::{self, TaskOptions};
+use kube::api::{Api, DynamicObject, ListParams};
+use prometheus::{IntCounterVec, IntGauge, IntGaugeVec, Opts};
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+/// Registry backing the `/metrics` endpoint.
+pub static REGISTRY: LazyLock<prometheus::Registry> = LazyLock::new(prometheus::Registry::new);
+
+/// Mutating requests waiting for a per-namespace concurrency slot.
+pub static NAMESPACE_QUEUE_DEPTH: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_gauge_vec(
+        "namespace_mutation_queue_depth",
+        "Mutating requests queued for a namespace concurrency slot",
+        &["namespace"],
+    )
+});
+
+fn register_gauge_vec(name: &str, help: &str, labels: &[&str]) -> IntGaugeVec {
+    // Metric names and labels are static, so construction cannot fail at runtime
+    let gauge = IntGaugeVec::new(Opts::new(name, help), labels).expect("valid metric definition");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("metric registered once");
+    gauge
+}
+
+/// Requests currently being handled; read at shutdown to report the ones cut off.
+pub static IN_FLIGHT_REQUESTS: LazyLock<IntGauge> = LazyLock::new(|| {
+    let gauge = IntGauge::new("http_requests_in_flight", "Requests currently being handled").expect("valid metric definition");
+    REGISTRY
+        .register(Box::new(gauge.clone()))
+        .expect("metric registered once");
+    gauge
+});
+
+/// Whether each supervised background task is running (1) or down (0).
+pub static BACKGROUND_TASK_UP: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_gauge_vec("background_task_up", "Whether the background task is running", &["task"])
+});
+
+/// Restarts of each supervised background task.
+pub static BACKGROUND_TASK_RESTARTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    let counter = IntCounterVec::new(
+        Opts::new("background_task_restarts_total", "Restarts of the background task"),
+        &["task"],
+    )
+    .expect("valid metric definition");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("metric registered once");
+    counter
+});
+
+/// Managed resources per namespace and resource type, refreshed by `spawn_resource_counter`.
+pub static MANAGED_RESOURCES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_gauge_vec(
+        "managed_resources",
+        "Resources of each enabled type per namespace",
+        &["namespace", "resource"],
+    )
+});
+
+/// Periodically counts each enabled resource type in `namespaces`.
+///
+/// Only the given namespaces are tracked, which bounds the label cardinality. A failed count
+/// keeps the previous value so a transient API error does not show up as a drop to zero.
+pub fn spawn_resource_counter(registry: &'static Registry, namespaces: Vec<String>, interval: Duration) {
+    let options = TaskOptions {
+        critical: false,
+        restart: true,
+    };
+    supervisor::spawn("resource-counter", options, move || count_resources(registry, namespaces.clone(), interval));
+}
+
+async fn count_resources(registry: &'static Registry, namespaces: Vec<String>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        let client = match resources::kube_client().await {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!(error = %e, "Skipping resource count refresh");
+                continue;
+            }
+        };
+
+        for module in registry.modules() {
+            let api_resource = module.api_resource();
+            for namespace in &namespaces {
+                let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), namespace, &api_resource);
+                match api.list_metadata(&ListParams::default()).await {
+                    Ok(list) => MANAGED_RESOURCES
+                        .with_label_values(&[namespace.as_str(), module.name()])
+                        .set(list.items.len() as i64),
+                    Err(e) => tracing::warn!(
+                        namespace = %namespace,
+                        resource = module.name(),
+                        error = %e,
+                        "Failed to count resources"
+                    ),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use crate::config::{self, Config};
+use crate::error::{AppError, Result};
+use crate::metrics;
+use crate::utils::namespace::NAMESPACE_HEADER;
+use crate::utils::{maintenance, timeout, warnings};
+use axum::{
+    extract::{Query, Request},
+    http::{header, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use tower_http::cors::CorsLayer;
+use uuid::Uuid;
+
+/// Header carrying the request id, taken from the caller when present.
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// The id of the request being handled, if called within `assign_request_id`.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Tags each request with an id (the caller's `X-Request-Id`, or a new UUID) and echoes it back.
+pub async fn assign_request_id(request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= 128)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let mut response = REQUEST_ID.scope(request_id.clone(), next.run(request)).await;
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Counts the request in `http_requests_in_flight` until its handler returns or is cancelled.
+pub async fn track_in_flight(request: Request, next: Next) -> Response {
+    struct InFlight;
+    impl Drop for InFlight {
+        fn drop(&mut self) {
+            metrics::IN_FLIGHT_REQUESTS.dec();
+        }
+    }
+
+    metrics::IN_FLIGHT_REQUESTS.inc();
+    let _in_flight = InFlight;
+    next.run(request).await
+}
+
+/// Reports bodies over `MAX_BODY_BYTES` with the standard error body.
+///
+/// The body limit answers 413 itself when `Content-Length` is too large, and body extractors do when a
+/// streamed body runs past it; both responses are plain text.
+pub async fn reject_oversized_bodies(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/json"));
+
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json {
+        return AppError::PayloadTooLarge(format!(
+            "Request body exceeds the limit of {} bytes",
+            config::get().max_body_bytes
+        ))
+        .into_response();
+    }
+    response
+}
+
+/// CORS policy from `ALLOWED_ORIGINS`: the listed origins may use the API's methods and headers.
+///
+/// Without allowed origins no CORS headers are sent, so browsers only allow same-origin calls.
+/// `CORS_PERMISSIVE` allows every origin instead.
+pub fn cors_layer(config: &Config) -> CorsLayer {
+    if config.cors_permissive {
+        return CorsLayer::permissive();
+    }
+
+    let origins: Vec<HeaderValue> = config
+        .allowed_origins
+        .iter()
+        .flatten()
+        .filter_map(|origin| HeaderValue::from_str(origin).ok())
+        .collect();
+    if origins.is_empty() {
+        return CorsLayer::new();
+    }
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE])
+        .allow_headers([
+            header::ACCEPT,
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            REQUEST_ID_HEADER,
+            NAMESPACE_HEADER,
+        ])
+        .expose_headers([header::LOCATION, header::RETRY_AFTER, REQUEST_ID_HEADER])
+}
+
+/// Collects the Kubernetes API warnings raised while handling the request, for handlers to return.
+pub async fn collect_kube_warnings(request: Request, next: Next) -> Response {
+    warnings::scope(next.run(request)).await
+}
+
+#[derive(Deserialize)]
+struct TimeoutQuery {
+    timeout: Option<String>,
+}
+
+/// Applies a `?timeout=` query parameter to the Kubernetes API calls made by the request.
+pub async fn apply_timeout_override(request: Request, next: Next) -> Result<Response> {
+    let override_timeout = Query::<TimeoutQuery>::try_from_uri(request.uri())
+        .ok()
+        .and_then(|Query(query)| query.timeout);
+
+    match override_timeout {
+        Some(value) => Ok(timeout::scope(timeout::parse(&value)?, next.run(request)).await),
+        None => Ok(next.run(request).await),
+    }
+}
+
+/// Media types accepted as request bodies on mutating requests.
+const ACCEPTED_MEDIA_TYPES: &[&str] = &[
+    "application/json",
+    "application/merge-patch+json",
+    "application/json-patch+json",
+    "application/yaml",
+    "application/x-yaml",
+    "text/yaml",
+];
+
+/// Rejects POST/PUT/PATCH requests whose body is not declared as JSON or YAML.
+///
+/// Requests without a body (e.g. action endpoints) pass through untouched, so only
+/// callers actually sending a payload need to set `Content-Type`.
+pub async fn require_json_content_type(request: Request, next: Next) -> Result<Response> {
+    let is_mutating = matches!(*request.method(), Method::POST | Method::PUT | Method::PATCH);
+
+    if is_mutating && has_body(&request) {
+        let content_type = request
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("");
+
+        if !is_accepted_media_type(content_type) {
+            return Err(AppError::BadRequest(format!(
+                "Unsupported Content-Type '{}': request bodies must be sent as application/json or application/yaml",
+                content_type
+            )));
+        }
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Rejects create/update/delete requests with 503 while read-only mode is on; reads pass through.
+pub async fn reject_mutations_when_read_only(request: Request, next: Next) -> Result<Response> {
+    let is_mutating = matches!(
+        *request.method(),
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    );
+
+    if is_mutating && maintenance::is_read_only() {
+        return Err(AppError::Unavailable(
+            "The service is in read-only maintenance mode; changes are rejected until it is lifted".to_string(),
+        ));
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Requires `Authorization: Bearer <ADMIN_TOKEN>`. Without a configured token the admin endpoints are disabled.
+pub async fn require_admin_token(request: Request, next: Next) -> Result<Response> {
+    let Some(expected) = config::get().admin_token.as_deref() else {
+        return Err(AppError::Forbidden("Admin endpoints are disabled; set ADMIN_TOKEN to enable them".to_string()));
+    };
+
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or("");
+
+    if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+        return Err(AppError::Unauthorized("A valid admin bearer token is required".to_string()));
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Compares secrets without returning early on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn has_body(request: &Request) -> bool {
+    let headers = request.headers();
+
+    let content_length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+
+    match content_length {
+        Some(length) => length > 0,
+        None => headers.contains_key(header::TRANSFER_ENCODING),
+    }
+}
+
+fn is_accepted_media_type(content_type: &str) -> bool {
+    let media_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+
+    ACCEPTED_MEDIA_TYPES.contains(&media_type.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_accepted_media_type() {
+        assert!(is_accepted_media_type("application/json"));
+        assert!(is_accepted_media_type("application/json; charset=utf-8"));
+        assert!(is_accepted_media_type("Application/JSON"));
+        assert!(is_accepted_media_type("application/merge-patch+json"));
+        assert!(is_accepted_media_type("application/yaml"));
+        assert!(!is_accepted_media_type("text/plain"));
+        assert!(!is_accepted_media_type("application/x-www-form-urlencoded"));
+        assert!(!is_accepted_media_type(""));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
+    #[tokio::test]
+    async fn test_cors_layer() {
+        use axum::{body::Body, routing::get, Router};
+        use tower::ServiceExt;
+
+        let preflight = |config: Config, origin: &'static str| async move {
+            let app = Router::new().route("/", get(|| async { "ok" })).layer(cors_layer(&config));
+            let request = Request::builder()
+                .method(Method::OPTIONS)
+                .uri("/")
+                .header(header::ORIGIN, origin)
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, "PATCH")
+                .body(Body::empty())
+                .unwrap();
+            app.oneshot(request).await.unwrap().headers().clone()
+        };
+        let allow_origin = |config: Config, origin: &'static str| async move {
+            preflight(config, origin).await.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).cloned()
+        };
+
+        assert_eq!(allow_origin(Config::default(), "https://ui.example.com").await, None);
+
+        let config = || Config {
+            allowed_origins: Some(vec!["https://ui.example.com".to_string()]),
+            ..Config::default()
+        };
+        assert_eq!(
+            allow_origin(config(), "https://ui.example.com").await,
+            Some(HeaderValue::from_static("https://ui.example.com"))
+        );
+        assert_eq!(allow_origin(config(), "https://evil.example.com").await, None);
+
+        // Every mutating method the API accepts passes the preflight
+        let headers = preflight(config(), "https://ui.example.com").await;
+        let methods = headers[header::ACCESS_CONTROL_ALLOW_METHODS].to_str().unwrap();
+        for method in ["GET", "POST", "PUT", "PATCH", "DELETE"] {
+            assert!(methods.contains(method), "{}", methods);
+        }
+
+        let permissive = Config {
+            cors_permissive: true,
+            ..Config::default()
+        };
+        assert_eq!(allow_origin(permissive, "https://evil.example.com").await, Some(HeaderValue::from_static("*")));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_bodies_get_the_error_body() {
+        use crate::utils::format::Payload;
+        use axum::{body::Body, routing::post, Router};
+        use serde_json::Value;
+        use tower::ServiceExt;
+        use tower_http::limit::RequestBodyLimitLayer;
+
+        let app = Router::new()
+            .route("/", post(|Payload(body): Payload<Value>| async move { axum::Json(body) }))
+            .layer(RequestBodyLimitLayer::new(16))
+            .layer(axum::middleware::from_fn(reject_oversized_bodies));
+        let send = |body: Body| {
+            let request = Request::builder()
+                .method(Method::POST)
+                .uri("/")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(body)
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        let large = format!(r#"{{"name": "{}"}}"#, "x".repeat(32));
+
+        // Rejected by the limit from Content-Length, and by the extractor for a streamed body
+        let chunks = vec![Ok::<_, std::io::Error>(large.clone())];
+        for body in [Body::from(large.clone()), Body::from_stream(futures::stream::iter(chunks))] {
+            let response = send(body).await.unwrap();
+            assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["error"]["type"], "PayloadTooLarge");
+        }
+
+        assert_eq!(send(Body::from(r#"{"a": 1}"#)).await.unwrap().status(), StatusCode::OK);
+    }
+}
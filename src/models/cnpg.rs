@@ -1,11 +1,11 @@
 use kube::CustomResource;
 use serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(CustomResource, Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[kube(group = "postgresql.cnpg.io", version = "v1", kind = "Cluster")]
-#[kube(namespaced)]
+#[kube(namespaced, status = "ClusterStatus")]
 pub struct ClusterSpec {
     pub instances: i32,
     pub postgresql: PostgreSQLConfig,
@@ -15,11 +15,143 @@ pub struct ClusterSpec {
     pub storage: Option<StorageConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub monitoring: Option<MonitoringConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "imageName")]
+    pub image_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "imageCatalogRef")]
+    pub image_catalog_ref: Option<ImageCatalogRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "ephemeralVolumesSizeLimit")]
+    pub ephemeral_volumes_size_limit: Option<EphemeralVolumesSizeLimitConfig>,
+    /// Seconds the primary may take to shut down gracefully during a switchover.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "switchoverDelay")]
+    pub switchover_delay: Option<i32>,
+    /// Seconds reserved for a smart shutdown before PostgreSQL is shut down fast.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "smartShutdownTimeout")]
+    pub smart_shutdown_timeout: Option<i32>,
+    /// Seconds the operator waits after detecting a failed primary before starting a failover.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "failoverDelay")]
+    pub failover_delay: Option<i32>,
+    /// Seconds an instance may take to shut down before it is killed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "stopDelay")]
+    pub stop_delay: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<Vec<ClusterEnvVar>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "priorityClassName")]
+    pub priority_class_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "topologySpreadConstraints")]
+    pub topology_spread_constraints: Option<Vec<TopologySpreadConstraintConfig>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct TopologySpreadConstraintConfig {
+    #[serde(rename = "maxSkew")]
+    pub max_skew: i32,
+    #[serde(rename = "topologyKey")]
+    pub topology_key: String,
+    #[serde(rename = "whenUnsatisfiable")]
+    pub when_unsatisfiable: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "labelSelector")]
+    pub label_selector: Option<LabelSelectorConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct LabelSelectorConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "matchLabels")]
+    pub match_labels: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ClusterEnvVar {
+    pub name: String,
+    pub value: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct PostgreSQLConfig {
     pub parameters: HashMap<String, String>,
+    /// `pg_ident.conf` user name maps, one `MAPNAME SYSTEM-USERNAME PG-USERNAME` line each.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pg_ident: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ldap: Option<LdapConfig>,
+    /// Seconds a replica may take to be promoted to primary during a failover or switchover.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "promotionTimeout")]
+    pub promotion_timeout: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synchronous: Option<SynchronousConfig>,
+}
+
+/// Synchronous replication; the primary waits for `number` standbys to confirm each commit.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SynchronousConfig {
+    /// `any` (quorum) or `first` (priority).
+    pub method: String,
+    pub number: i32,
+    /// Only fail over to a replica known to have every synchronously committed transaction (CNPG 1.27+).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "failoverQuorum")]
+    pub failover_quorum: Option<bool>,
+}
+
+/// LDAP authentication for PostgreSQL users, in simple bind or search+bind mode.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct LdapConfig {
+    pub server: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "bindAsAuth")]
+    pub bind_as_auth: Option<LdapBindAsAuth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "bindSearchAuth")]
+    pub bind_search_auth: Option<LdapBindSearchAuth>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct LdapBindAsAuth {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct LdapBindSearchAuth {
+    #[serde(rename = "baseDN")]
+    pub base_dn: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "bindDN")]
+    pub bind_dn: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "bindPassword")]
+    pub bind_password: Option<SecretKeySelectorConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "searchAttribute")]
+    pub search_attribute: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "searchFilter")]
+    pub search_filter: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SecretKeySelectorConfig {
+    pub name: String,
+    pub key: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
@@ -55,11 +187,150 @@ pub struct MonitoringConfig {
     pub disable_default_queries: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct EphemeralVolumesSizeLimitConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "temporaryData")]
+    pub temporary_data: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shm: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ImageCatalogRef {
+    #[serde(rename = "apiGroup")]
+    pub api_group: String,
+    pub kind: String,
+    pub name: String,
+    pub major: i32,
+}
+
+/// Cluster state reported by the CNPG operator; empty until the operator has reconciled the cluster.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default)]
+pub struct ClusterStatus {
+    /// e.g. `Cluster in healthy state` or `Setting up primary`.
+    pub phase: Option<String>,
+    #[serde(rename = "readyInstances")]
+    pub ready_instances: Option<i32>,
+    /// Pod name of the current primary.
+    #[serde(rename = "currentPrimary")]
+    pub current_primary: Option<String>,
+}
+
+/// A database managed declaratively inside a CNPG cluster (CNPG 1.25+).
+#[derive(CustomResource, Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[kube(group = "postgresql.cnpg.io", version = "v1", kind = "Database")]
+#[kube(namespaced, status = "DatabaseStatus")]
+pub struct DatabaseSpec {
+    pub cluster: ClusterReference,
+    /// Name of the database in PostgreSQL.
+    pub name: String,
+    pub owner: String,
+    /// `retain` (default) keeps the database in PostgreSQL when this object is deleted; `delete` drops it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "databaseReclaimPolicy")]
+    pub database_reclaim_policy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ClusterReference {
+    pub name: String,
+}
+
+/// On-demand backup of a CNPG cluster; the operator runs it once after the object is created.
+#[derive(CustomResource, Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[kube(group = "postgresql.cnpg.io", version = "v1", kind = "Backup")]
+#[kube(namespaced, status = "BackupStatus")]
+pub struct BackupSpec {
+    pub cluster: ClusterReference,
+    /// `barmanObjectStore` (the operator's default), `volumeSnapshot` or `plugin`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// `primary` or `prefer-standby`; defaults to the cluster's backup target.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default)]
+pub struct BackupStatus {
+    /// e.g. `started`, `completed` or `failed`.
+    pub phase: Option<String>,
+    #[serde(rename = "startedAt")]
+    pub started_at: Option<String>,
+    #[serde(rename = "stoppedAt")]
+    pub stopped_at: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Recurring backup of a CNPG cluster; the operator creates a `Backup` each time the schedule fires.
+#[derive(CustomResource, Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[kube(group = "postgresql.cnpg.io", version = "v1", kind = "ScheduledBackup")]
+#[kube(namespaced, status = "ScheduledBackupStatus")]
+pub struct ScheduledBackupSpec {
+    /// Six-field cron expression, seconds first.
+    pub schedule: String,
+    pub cluster: ClusterReference,
+    /// Owner of the created backups: `none`, `self` or `cluster`.
+    #[serde(rename = "backupOwnerReference", skip_serializing_if = "Option::is_none")]
+    pub backup_owner_reference: Option<String>,
+    /// Take the first backup right away instead of waiting for the schedule.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub immediate: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suspend: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default)]
+pub struct ScheduledBackupStatus {
+    #[serde(rename = "lastCheckTime")]
+    pub last_check_time: Option<String>,
+    #[serde(rename = "lastScheduleTime")]
+    pub last_schedule_time: Option<String>,
+    #[serde(rename = "nextScheduleTime")]
+    pub next_schedule_time: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct DatabaseStatus {
+    /// Whether the operator reconciled the database in PostgreSQL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applied: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CreateDatabaseRequest {
+    /// Name of the `Database` object.
+    pub name: String,
+    pub namespace: Option<String>,
+    /// Cluster the database is created in; must exist in the same namespace.
+    pub cluster: String,
+    /// Name of the database in PostgreSQL; defaults to `name`.
+    pub database_name: Option<String>,
+    pub owner: String,
+    /// `retain` (default) or `delete`.
+    pub reclaim_policy: Option<String>,
+    pub encoding: Option<String>,
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct CreateClusterRequest {
     pub name: String,
     pub namespace: Option<String>,
-    pub instances: i32,
+    pub instances: Option<i32>,
     pub database_name: String,
     pub database_owner: String,
     pub secret_name: String,
@@ -67,11 +338,133 @@ pub struct CreateClusterRequest {
     pub storage_class: Option<String>,
     pub postgresql_parameters: Option<HashMap<String, String>>,
     pub monitoring_enabled: Option<bool>,
+    /// Scrape metrics through a `ServiceMonitor` instead of CNPG's `PodMonitor`; requires `monitoring_enabled`.
+    pub service_monitor: Option<bool>,
+    pub ephemeral_volumes_size_limit: Option<EphemeralVolumesSizeLimitRequest>,
+    pub env: Option<HashMap<String, String>>,
+    pub switchover_delay: Option<i64>,
+    pub smart_shutdown_timeout: Option<i64>,
+    /// Seconds a replica may take to be promoted; raise it for large databases.
+    pub promotion_timeout: Option<i64>,
+    /// Seconds to wait after the primary fails before failing over.
+    pub failover_delay: Option<i64>,
+    /// Seconds an instance may take to shut down; CNPG defaults to 1800.
+    pub stop_delay: Option<i64>,
+    /// Standbys that must confirm each commit (quorum-based synchronous replication); fewer than `instances`.
+    pub synchronous_replicas: Option<i32>,
+    /// Quorum-based failover: only promote a replica that has every synchronously committed transaction.
+    /// Requires `synchronous_replicas`.
+    pub failover_quorum: Option<bool>,
+    pub pg_ident: Option<Vec<String>>,
+    pub priority_class_name: Option<String>,
+    pub topology_spread_constraints: Option<Vec<TopologySpreadConstraintRequest>>,
+    pub ldap: Option<LdapRequest>,
+    /// Labels of the `Cluster` object; they take precedence over labels propagated from the namespace.
+    pub labels: Option<HashMap<String, String>>,
+    pub annotations: Option<HashMap<String, String>>,
+}
+
+/// LDAP authentication settings. Set `base_dn` for search+bind, or `bind_prefix`/`bind_suffix` for simple bind.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct LdapRequest {
+    pub server: String,
+    pub port: Option<i32>,
+    /// `ldap` or `ldaps`.
+    pub scheme: Option<String>,
+    /// Use StartTLS on an `ldap` connection.
+    pub tls: Option<bool>,
+    /// Search base for search+bind.
+    pub base_dn: Option<String>,
+    /// DN to bind as while searching; anonymous search when omitted.
+    pub bind_dn: Option<String>,
+    /// Secret holding the password of `bind_dn`.
+    pub bind_password_secret: Option<String>,
+    /// Key of the password in `bind_password_secret` (default `password`).
+    pub bind_password_key: Option<String>,
+    /// Attribute matched against the user name, e.g. `uid`.
+    pub search_attribute: Option<String>,
+    /// Search filter, e.g. `(uid=$username)`; cannot be combined with `search_attribute`.
+    pub search_filter: Option<String>,
+    pub bind_prefix: Option<String>,
+    pub bind_suffix: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TopologySpreadConstraintRequest {
+    pub max_skew: i32,
+    /// Node label to spread over, e.g. `topology.kubernetes.io/zone`.
+    pub topology_key: String,
+    /// `DoNotSchedule` (default) or `ScheduleAnyway`.
+    pub when_unsatisfiable: Option<String>,
+    /// Pods counted for the skew; defaults to the cluster's own instances.
+    pub match_labels: Option<HashMap<String, String>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct EphemeralVolumesSizeLimitRequest {
+    pub temporary_data: Option<String>,
+    pub shm: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ScaleClusterRequest {
+    pub instances: i32,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct UpdateClusterRequest {
+    /// Optional; must match the namespace in the path when given.
+    pub namespace: Option<String>,
     pub instances: Option<i32>,
     pub postgresql_parameters: Option<HashMap<String, String>>,
     pub monitoring_enabled: Option<bool>,
-}
\ No newline at end of file
+    pub switchover_delay: Option<i64>,
+    pub smart_shutdown_timeout: Option<i64>,
+    pub promotion_timeout: Option<i64>,
+    pub failover_delay: Option<i64>,
+    pub stop_delay: Option<i64>,
+    pub synchronous_replicas: Option<i32>,
+    pub failover_quorum: Option<bool>,
+    /// Replaces the `pg_ident` maps; an empty list removes them.
+    pub pg_ident: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct UpgradeClusterRequest {
+    pub target_version: u32,
+    pub image_name: Option<String>,
+    pub image_catalog: Option<String>,
+    pub image_catalog_kind: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TriggerBackupQuery {
+    /// Backup name; defaults to `<cluster>-<timestamp>`.
+    pub name: Option<String>,
+    /// `barmanObjectStore`, `volumeSnapshot` or `plugin`; the operator's default when omitted.
+    pub method: Option<String>,
+    /// `primary` or `prefer-standby`; the cluster's backup target when omitted.
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CreateScheduledBackupRequest {
+    /// Name of the `ScheduledBackup`; defaults to `<cluster>-scheduled`.
+    pub name: Option<String>,
+    /// Six-field cron expression, seconds first, e.g. `0 0 2 * * *` for 02:00 every day.
+    pub schedule: String,
+    /// `none` (default), `self` or `cluster`; with `cluster` the backups are deleted along with the cluster.
+    #[serde(alias = "backupOwnerReference")]
+    pub backup_owner_reference: Option<String>,
+    pub immediate: Option<bool>,
+    pub suspend: Option<bool>,
+    pub method: Option<String>,
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RotateCredentialsQuery {
+    /// Return the new password in the response. It is never retrievable afterwards.
+    #[serde(default)]
+    pub reveal: bool,
+}
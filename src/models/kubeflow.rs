@@ -1,7 +1,9 @@
+use crate::models::nullable;
+use crate::utils::quantity;
 use kube::CustomResource;
 use serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(CustomResource, Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[kube(group = "kubeflow.org", version = "v1", kind = "Notebook")]
@@ -23,6 +25,26 @@ pub struct NotebookPodSpec {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "serviceAccountName")]
     pub service_account_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "nodeSelector")]
+    pub node_selector: Option<BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tolerations: Option<Vec<NotebookToleration>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub struct NotebookToleration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effect: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "tolerationSeconds", alias = "toleration_seconds")]
+    pub toleration_seconds: Option<i64>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
@@ -30,6 +52,15 @@ pub struct NotebookContainer {
     pub name: String,
     pub image: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "imagePullPolicy")]
+    pub image_pull_policy: Option<String>,
+    /// Replaces the image's entrypoint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+    /// Replaces the image's default arguments (`CMD`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub resources: Option<NotebookResources>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub env: Option<Vec<NotebookEnvVar>>,
@@ -46,6 +77,17 @@ pub struct NotebookResources {
     pub requests: Option<HashMap<String, String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limits: Option<HashMap<String, String>>,
+    /// Dynamic resource allocation claims, by name from the pod's `resourceClaims`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claims: Option<Vec<NotebookResourceClaim>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub struct NotebookResourceClaim {
+    pub name: String,
+    /// A single request of the claim; the whole claim is used when omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
@@ -89,29 +131,153 @@ pub struct NotebookPort {
     pub protocol: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct CreateNotebookRequest {
     pub name: String,
     pub namespace: Option<String>,
-    pub image: String,
+    pub image: Option<String>,
+    pub image_pull_policy: Option<String>,
+    /// Entrypoint for images that do not start a notebook server on their own.
+    pub command: Option<Vec<String>>,
+    pub args: Option<Vec<String>>,
     pub cpu_request: Option<String>,
     pub cpu_limit: Option<String>,
     pub memory_request: Option<String>,
     pub memory_limit: Option<String>,
     pub gpu_limit: Option<String>,
+    /// DRA claims for the notebook container, e.g. for accelerators not exposed as `nvidia.com/gpu`.
+    pub resource_claims: Option<Vec<NotebookResourceClaim>>,
+    /// Node labels the pod must match, e.g. `nvidia.com/gpu.product: NVIDIA-A100-SXM4-80GB` to pick a GPU pool.
+    pub node_selector: Option<HashMap<String, String>>,
+    pub tolerations: Option<Vec<NotebookToleration>>,
+    /// Add the GPU node toleration when `gpu_limit` is set (default `true`).
+    pub auto_gpu_toleration: Option<bool>,
     pub workspace_volume_size: Option<String>,
     pub workspace_volume_mount: Option<String>,
+    /// Storage class of a new workspace PVC; the cluster default when omitted.
+    pub workspace_storage_class: Option<String>,
+    /// Create a workspace PVC, of `DEFAULT_WORKSPACE_SIZE` unless `workspace_volume_size` is given.
+    pub create_workspace: Option<bool>,
+    pub existing_workspace_pvc: Option<String>,
     pub environment_variables: Option<HashMap<String, String>>,
     pub service_account: Option<String>,
+    pub labels: Option<HashMap<String, String>>,
+    pub annotations: Option<HashMap<String, String>>,
+    /// Exempts the notebook from idle culling when `true`.
+    pub disable_culling: Option<bool>,
+    /// Rewrite CPU, memory and workspace sizes to canonical units, e.g. `1000m` → `1` (default `false`).
+    pub normalize_quantities: Option<bool>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct UpdateNotebookRequest {
+    /// Optional; must match the namespace in the path when given.
+    pub namespace: Option<String>,
     pub image: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub image_pull_policy: Option<Option<String>>,
+    /// Replaces the command; an empty list restores the image's entrypoint.
+    pub command: Option<Vec<String>>,
+    /// Replaces the arguments; an empty list restores the image's defaults.
+    pub args: Option<Vec<String>>,
     pub cpu_request: Option<String>,
     pub cpu_limit: Option<String>,
     pub memory_request: Option<String>,
     pub memory_limit: Option<String>,
     pub gpu_limit: Option<String>,
     pub environment_variables: Option<HashMap<String, String>>,
-}
\ No newline at end of file
+    /// Rewrite CPU and memory values to canonical units, e.g. `1024Mi` → `1Gi` (default `false`).
+    pub normalize_quantities: Option<bool>,
+}
+
+impl CreateNotebookRequest {
+    /// Applies `normalize_quantities`; values that cannot be parsed exactly are left as sent.
+    pub fn normalize_quantities(&mut self) {
+        if self.normalize_quantities != Some(true) {
+            return;
+        }
+        for cpu in [&mut self.cpu_request, &mut self.cpu_limit].into_iter().flatten() {
+            normalize(cpu, quantity::normalize_cpu);
+        }
+        for bytes in [&mut self.memory_request, &mut self.memory_limit, &mut self.workspace_volume_size]
+            .into_iter()
+            .flatten()
+        {
+            normalize(bytes, quantity::normalize_bytes);
+        }
+    }
+}
+
+impl UpdateNotebookRequest {
+    /// Applies `normalize_quantities`; values that cannot be parsed exactly are left as sent.
+    pub fn normalize_quantities(&mut self) {
+        if self.normalize_quantities != Some(true) {
+            return;
+        }
+        for cpu in [&mut self.cpu_request, &mut self.cpu_limit].into_iter().flatten() {
+            normalize(cpu, quantity::normalize_cpu);
+        }
+        for bytes in [&mut self.memory_request, &mut self.memory_limit].into_iter().flatten() {
+            normalize(bytes, quantity::normalize_bytes);
+        }
+    }
+}
+
+fn normalize(value: &mut String, canonical: fn(&str) -> Option<String>) {
+    if let Some(normalized) = canonical(value) {
+        *value = normalized;
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CreateSnapshotQuery {
+    /// Snapshot name; defaults to `<notebook>-<timestamp>`.
+    pub name: Option<String>,
+    /// `VolumeSnapshotClass` to use; defaults to `VOLUME_SNAPSHOT_CLASS`, then the cluster default.
+    pub snapshot_class: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ResizeWorkspaceQuery {
+    /// New size of the workspace PVC, e.g. `20Gi`; must be larger than the current size.
+    pub size: String,
+}
+
+/// CSI volume snapshot, served by the external snapshotter's CRDs.
+#[derive(CustomResource, Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[kube(group = "snapshot.storage.k8s.io", version = "v1", kind = "VolumeSnapshot")]
+#[kube(namespaced, status = "VolumeSnapshotStatus")]
+pub struct VolumeSnapshotSpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "volumeSnapshotClassName")]
+    pub volume_snapshot_class_name: Option<String>,
+    pub source: VolumeSnapshotSource,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct VolumeSnapshotSource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "persistentVolumeClaimName")]
+    pub persistent_volume_claim_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "volumeSnapshotContentName")]
+    pub volume_snapshot_content_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct VolumeSnapshotStatus {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "readyToUse")]
+    pub ready_to_use: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "restoreSize")]
+    pub restore_size: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<VolumeSnapshotError>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct VolumeSnapshotError {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
@@ -1,25 +1,149 @@
 pub mod cnpg;
 pub mod kubeflow;
+pub mod monitoring;
 
-use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct ListQuery {
     pub namespace: Option<String>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+    /// Raw Kubernetes continue token from a previous page's `continue`; an alternative to `cursor`.
+    pub continue_token: Option<String>,
+    /// Kubernetes label selector, e.g. `app=db,tier in (primary,replica)`.
+    pub label_selector: Option<String>,
+    /// RFC 3339 timestamp; only items created at or after it are returned.
+    pub created_after: Option<String>,
+    /// RFC 3339 timestamp; only items created before it are returned.
+    pub created_before: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct ResourceInfo {
-    pub name: Option<String>,
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CreateQuery {
+    /// Succeed with `"created": false` when an equivalent resource already exists.
+    #[serde(default)]
+    pub if_not_exists: bool,
+    /// `strict`, `warn` (default) or `ignore`: how the API server treats unknown or duplicate fields.
+    pub field_validation: Option<String>,
+    /// `true` runs the create as a server-side dry run and returns the object the server would store.
+    pub dry_run: Option<String>,
+}
+
+impl CreateQuery {
+    pub fn dry_run(&self) -> crate::error::Result<bool> {
+        match DryRun::parse(self.dry_run.as_deref())? {
+            DryRun::Diff => Err(crate::error::AppError::BadRequest(
+                "dry_run=diff is only supported on updates; use dry_run=true".to_string(),
+            )),
+            mode => Ok(mode == DryRun::Object),
+        }
+    }
+}
+
+/// How a create or update runs against the API server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DryRun {
+    /// Persist the change.
+    Off,
+    /// Server-side dry run returning the object the server would store.
+    Object,
+    /// Server-side dry run returning the changes it would make.
+    Diff,
+}
+
+impl DryRun {
+    fn parse(value: Option<&str>) -> crate::error::Result<Self> {
+        match value {
+            None | Some("false") => Ok(Self::Off),
+            Some("true") => Ok(Self::Object),
+            Some("diff") => Ok(Self::Diff),
+            Some(other) => Err(crate::error::AppError::BadRequest(format!(
+                "Unsupported dry_run value '{}': expected 'true', 'false' or 'diff'",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DeleteQuery {
+    /// `Orphan`, `Background` or `Foreground`; defaults to `DEFAULT_PROPAGATION_POLICY`.
+    pub propagation_policy: Option<String>,
+    /// Quarantine and scale down the resource instead of deleting it (see `resources::quarantine`).
+    #[serde(default)]
+    pub soft: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BatchDeleteRequest {
+    pub items: Vec<BatchDeleteItem>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BatchDeleteItem {
+    /// Resource type as in `/resources`, e.g. `cnpg` or `kubeflow`.
+    pub resource: String,
+    pub namespace: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct NamespaceQuery {
     pub namespace: Option<String>,
-    pub resource_type: String,
-    pub creation_timestamp: Option<String>,
-    pub status: Option<String>,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WatchQuery {
+    pub namespace: Option<String>,
+    /// Kubernetes label selector, e.g. `app=db,tier in (primary,replica)`.
+    pub label_selector: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct UpdateQuery {
+    /// `true` runs the update as a server-side dry run and returns the object the server would store;
+    /// `diff` returns the changes it would make instead.
+    pub dry_run: Option<String>,
+    /// `strict`, `warn` (default) or `ignore`: how the API server treats unknown or duplicate fields.
+    pub field_validation: Option<String>,
+    /// Take over fields owned by other field managers on server-side apply updates (notebooks only).
+    #[serde(default)]
+    pub force_ownership: bool,
+}
+
+impl UpdateQuery {
+    pub fn dry_run(&self) -> crate::error::Result<DryRun> {
+        DryRun::parse(self.dry_run.as_deref())
+    }
+}
+
+/// Envelope of the responses of mutating endpoints (unless `LEGACY_RESPONSES` is set).
 #[derive(Debug, Serialize)]
-pub struct ResourceList {
-    pub resources: Vec<ResourceInfo>,
-    pub count: usize,
+pub struct OperationResponse<T> {
+    pub success: bool,
+    /// e.g. `cnpg-cluster` or `kubeflow-notebook`.
     pub resource_type: String,
-}
\ No newline at end of file
+    pub data: T,
+    /// Kubernetes API and service warnings for the request; omitted when there are none.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReadOnlyRequest {
+    pub enabled: bool,
+}
+
+/// Deserializes an update field where an explicit `null` means "clear" rather than "leave unchanged".
+///
+/// Use with `#[serde(default, deserialize_with = "nullable")]` on an `Option<Option<T>>`:
+/// an absent field is `None`, `null` is `Some(None)` and a value is `Some(Some(value))`.
+pub fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
@@ -0,0 +1,29 @@
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Prometheus operator scrape configuration for the endpoints of matching services.
+#[derive(CustomResource, Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[kube(group = "monitoring.coreos.com", version = "v1", kind = "ServiceMonitor")]
+#[kube(namespaced)]
+pub struct ServiceMonitorSpec {
+    pub selector: ServiceMonitorSelector,
+    pub endpoints: Vec<ServiceMonitorEndpoint>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ServiceMonitorSelector {
+    #[serde(rename = "matchLabels")]
+    pub match_labels: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ServiceMonitorEndpoint {
+    /// Name of the service port to scrape.
+    pub port: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<String>,
+}
@@ -0,0 +1,382 @@
+use crate::error::{AppError, Result};
+use crate::handlers::{cnpg, kubeflow};
+use crate::models::cnpg::{
+    CreateClusterRequest, CreateDatabaseRequest, CreateScheduledBackupRequest, Database, RotateCredentialsQuery,
+    ScaleClusterRequest, TriggerBackupQuery, UpdateClusterRequest, UpgradeClusterRequest,
+};
+use crate::models::kubeflow::{
+    CreateNotebookRequest, CreateSnapshotQuery, ResizeWorkspaceQuery, UpdateNotebookRequest,
+};
+use crate::models::{cnpg::Cluster, kubeflow::Notebook, CreateQuery, DeleteQuery, ListQuery, UpdateQuery, WatchQuery};
+use crate::resources::cnpg::CnpgManager;
+use crate::resources::kubeflow::{KubeflowManager, STOPPED_ANNOTATION};
+use crate::resources::ResourceManager;
+use crate::utils::openapi::ApiDoc;
+use axum::{routing, Router};
+use k8s_openapi::chrono::Utc;
+use kube::api::DeleteParams;
+use kube::{core::ApiResource, Client, Resource};
+use serde_json::{json, Value};
+use std::sync::OnceLock;
+
+/// A resource type the service can manage: its identity and the routes serving it.
+///
+/// `ResourceManager` has associated request types, so it cannot be boxed directly; each
+/// module wraps one manager and exposes only what the registry needs.
+#[async_trait::async_trait]
+pub trait ResourceModule: Send + Sync {
+    /// Identifier used in `ENABLED_RESOURCES` and `/resources`, e.g. `cnpg`.
+    fn name(&self) -> &'static str;
+    /// Base path of the module's collection endpoint.
+    fn path(&self) -> &'static str;
+    fn kind(&self) -> String;
+    fn api_version(&self) -> String;
+    /// Plural resource name served under `api_version`, used to check the CRD is installed.
+    fn plural(&self) -> String;
+    /// Type information for untyped access, e.g. counting resources without deserializing them.
+    fn api_resource(&self) -> ApiResource;
+    /// Annotation that scales the resource down without deleting it; used by soft delete.
+    fn suspend_annotation(&self) -> (&'static str, String);
+    /// Deletes a resource through its manager, together with anything the manager cleans up with it.
+    async fn delete(&self, client: Client, namespace: &str, name: &str, params: &DeleteParams) -> Result<Value>;
+    fn routes(&self) -> Router;
+    /// Adds the module's routes to the OpenAPI description; keep in step with `routes`.
+    fn document(&self, doc: &mut ApiDoc);
+
+    /// `resource_type` of the module's responses, e.g. `cnpg-cluster`.
+    fn resource_type(&self) -> String {
+        format!("{}-{}", self.name(), self.kind().to_lowercase())
+    }
+
+    fn describe(&self) -> Value {
+        json!({
+            "name": self.name(),
+            "kind": self.kind(),
+            "api_version": self.api_version(),
+            "path": self.path()
+        })
+    }
+}
+
+struct CnpgModule;
+
+#[async_trait::async_trait]
+impl ResourceModule for CnpgModule {
+    fn name(&self) -> &'static str {
+        "cnpg"
+    }
+
+    fn path(&self) -> &'static str {
+        "/cnpg/clusters"
+    }
+
+    fn kind(&self) -> String {
+        Cluster::kind(&()).into_owned()
+    }
+
+    fn api_version(&self) -> String {
+        Cluster::api_version(&()).into_owned()
+    }
+
+    fn plural(&self) -> String {
+        Cluster::plural(&()).into_owned()
+    }
+
+    fn api_resource(&self) -> ApiResource {
+        ApiResource::erase::<Cluster>(&())
+    }
+
+    fn suspend_annotation(&self) -> (&'static str, String) {
+        ("cnpg.io/hibernation", "on".to_string())
+    }
+
+    async fn delete(&self, client: Client, namespace: &str, name: &str, params: &DeleteParams) -> Result<Value> {
+        CnpgManager.delete(client, namespace, name, params).await
+    }
+
+    fn routes(&self) -> Router {
+        Router::new()
+            .merge(cluster_routes("/cnpg/clusters"))
+            .route("/cnpg/clusters/preflight", routing::post(cnpg::preflight_cluster))
+            .route("/cnpg/clusters/watch", routing::get(cnpg::watch_clusters))
+            .route("/cnpg/clusters/:namespace/:name/scale", routing::put(cnpg::scale_cluster))
+            .route("/cnpg/clusters/:namespace/:name/upgrade", routing::post(cnpg::upgrade_cluster))
+            .route("/cnpg/clusters/:namespace/:name/diagnose", routing::get(cnpg::diagnose_cluster))
+            .route("/cnpg/clusters/:namespace/:name/storage", routing::get(cnpg::cluster_storage))
+            .route("/cnpg/clusters/:namespace/:name/rotate-credentials", routing::post(cnpg::rotate_credentials))
+            .route("/cnpg/clusters/:namespace/:name/backup", routing::post(cnpg::trigger_backup))
+            .route(
+                "/cnpg/clusters/:namespace/:name/scheduled-backups",
+                routing::post(cnpg::create_scheduled_backup).get(cnpg::list_scheduled_backups),
+            )
+            .route("/cnpg/databases", routing::post(cnpg::create_database).get(cnpg::list_databases))
+            .route(
+                "/cnpg/databases/:namespace/:name",
+                routing::get(cnpg::get_database).delete(cnpg::delete_database),
+            )
+            // Legacy routes for backward compatibility (will be deprecated)
+            .merge(cluster_routes("/clusters"))
+    }
+
+    fn document(&self, doc: &mut ApiDoc) {
+        doc.operation("post", "/cnpg/clusters", "Create a PostgreSQL cluster")
+            .query::<CreateQuery>()
+            .body::<CreateClusterRequest>();
+        doc.operation("get", "/cnpg/clusters", "List clusters").query::<ListQuery>();
+        doc.operation("post", "/cnpg/clusters/preflight", "Check a cluster request against the target cluster")
+            .body::<CreateClusterRequest>();
+        doc.operation("get", "/cnpg/clusters/watch", "Stream cluster changes as Server-Sent Events")
+            .query::<WatchQuery>();
+        doc.operation("get", "/cnpg/clusters/:namespace/:name", "Get a cluster").returns::<Cluster>();
+        doc.operation("put", "/cnpg/clusters/:namespace/:name", "Update a cluster")
+            .query::<UpdateQuery>()
+            .body::<UpdateClusterRequest>();
+        doc.operation("delete", "/cnpg/clusters/:namespace/:name", "Delete a cluster").query::<DeleteQuery>();
+        doc.operation("put", "/cnpg/clusters/:namespace/:name/scale", "Scale a cluster")
+            .body::<ScaleClusterRequest>();
+        doc.operation("post", "/cnpg/clusters/:namespace/:name/upgrade", "Upgrade the PostgreSQL major version")
+            .body::<UpgradeClusterRequest>();
+        doc.operation("get", "/cnpg/clusters/:namespace/:name/diagnose", "Diagnose a cluster");
+        doc.operation("get", "/cnpg/clusters/:namespace/:name/storage", "Report volume usage of a cluster");
+        doc.operation("post", "/cnpg/clusters/:namespace/:name/rotate-credentials", "Generate a new application password")
+            .query::<RotateCredentialsQuery>();
+        doc.operation("post", "/cnpg/clusters/:namespace/:name/backup", "Start an on-demand backup")
+            .query::<TriggerBackupQuery>();
+        doc.operation("post", "/cnpg/clusters/:namespace/:name/scheduled-backups", "Schedule recurring backups")
+            .body::<CreateScheduledBackupRequest>();
+        doc.operation("get", "/cnpg/clusters/:namespace/:name/scheduled-backups", "List the scheduled backups of a cluster");
+        doc.operation("post", "/cnpg/databases", "Create a database in a cluster").body::<CreateDatabaseRequest>();
+        doc.operation("get", "/cnpg/databases", "List databases").query::<ListQuery>();
+        doc.operation("get", "/cnpg/databases/:namespace/:name", "Get a database").returns::<Database>();
+        doc.operation("delete", "/cnpg/databases/:namespace/:name", "Delete a database").query::<DeleteQuery>();
+    }
+}
+
+/// Create, list, get, update and delete of clusters under `prefix`; served at the canonical and the legacy path.
+fn cluster_routes(prefix: &str) -> Router {
+    Router::new()
+        .route(prefix, routing::post(cnpg::create_cluster).get(cnpg::list_clusters))
+        .route(
+            &format!("{}/:namespace/:name", prefix),
+            routing::get(cnpg::get_cluster).put(cnpg::update_cluster).delete(cnpg::delete_cluster),
+        )
+}
+
+struct KubeflowModule;
+
+#[async_trait::async_trait]
+impl ResourceModule for KubeflowModule {
+    fn name(&self) -> &'static str {
+        "kubeflow"
+    }
+
+    fn path(&self) -> &'static str {
+        "/kubeflow/notebooks"
+    }
+
+    fn kind(&self) -> String {
+        Notebook::kind(&()).into_owned()
+    }
+
+    fn api_version(&self) -> String {
+        Notebook::api_version(&()).into_owned()
+    }
+
+    fn plural(&self) -> String {
+        Notebook::plural(&()).into_owned()
+    }
+
+    fn api_resource(&self) -> ApiResource {
+        ApiResource::erase::<Notebook>(&())
+    }
+
+    fn suspend_annotation(&self) -> (&'static str, String) {
+        // The notebook controller scales the StatefulSet to zero while this is set
+        (STOPPED_ANNOTATION, Utc::now().to_rfc3339())
+    }
+
+    async fn delete(&self, client: Client, namespace: &str, name: &str, params: &DeleteParams) -> Result<Value> {
+        // Also removes the notebook's workspace PVC
+        KubeflowManager.delete(client, namespace, name, params).await
+    }
+
+    fn routes(&self) -> Router {
+        Router::new()
+            .route("/kubeflow/notebooks", routing::post(kubeflow::create_notebook).get(kubeflow::list_notebooks))
+            .route("/kubeflow/notebooks/watch", routing::get(kubeflow::watch_notebooks))
+            .route(
+                "/kubeflow/notebooks/:namespace/:name",
+                routing::get(kubeflow::get_notebook)
+                    .put(kubeflow::update_notebook)
+                    .delete(kubeflow::delete_notebook),
+            )
+            .route("/kubeflow/notebooks/:namespace/:name/snapshot", routing::post(kubeflow::create_workspace_snapshot))
+            .route("/kubeflow/notebooks/:namespace/:name/snapshots", routing::get(kubeflow::list_workspace_snapshots))
+            .route("/kubeflow/notebooks/:namespace/:name/workspace", routing::put(kubeflow::resize_workspace))
+            .route("/kubeflow/notebooks/:namespace/:name/restart", routing::post(kubeflow::restart_notebook))
+            .route("/kubeflow/notebooks/:namespace/:name/stop", routing::post(kubeflow::stop_notebook))
+            .route("/kubeflow/notebooks/:namespace/:name/start", routing::post(kubeflow::start_notebook))
+            .route(
+                "/kubeflow/notebooks/:namespace/:name/debug-pod",
+                routing::post(kubeflow::create_debug_pod).delete(kubeflow::delete_debug_pod),
+            )
+    }
+
+    fn document(&self, doc: &mut ApiDoc) {
+        doc.operation("post", "/kubeflow/notebooks", "Create a notebook")
+            .query::<CreateQuery>()
+            .body::<CreateNotebookRequest>();
+        doc.operation("get", "/kubeflow/notebooks", "List notebooks").query::<ListQuery>();
+        doc.operation("get", "/kubeflow/notebooks/watch", "Stream notebook changes as Server-Sent Events")
+            .query::<WatchQuery>();
+        doc.operation("get", "/kubeflow/notebooks/:namespace/:name", "Get a notebook with its pod status")
+            .returns::<Notebook>();
+        doc.operation("put", "/kubeflow/notebooks/:namespace/:name", "Update a notebook")
+            .query::<UpdateQuery>()
+            .body::<UpdateNotebookRequest>();
+        doc.operation("delete", "/kubeflow/notebooks/:namespace/:name", "Delete a notebook").query::<DeleteQuery>();
+        doc.operation("post", "/kubeflow/notebooks/:namespace/:name/snapshot", "Snapshot the workspace volume")
+            .query::<CreateSnapshotQuery>();
+        doc.operation("get", "/kubeflow/notebooks/:namespace/:name/snapshots", "List workspace snapshots");
+        doc.operation("put", "/kubeflow/notebooks/:namespace/:name/workspace", "Grow the workspace volume")
+            .query::<ResizeWorkspaceQuery>();
+        doc.operation("post", "/kubeflow/notebooks/:namespace/:name/restart", "Restart the notebook pod");
+        doc.operation("post", "/kubeflow/notebooks/:namespace/:name/stop", "Stop a notebook");
+        doc.operation("post", "/kubeflow/notebooks/:namespace/:name/start", "Start a stopped notebook");
+        doc.operation("post", "/kubeflow/notebooks/:namespace/:name/debug-pod", "Start a debug pod on the workspace");
+        doc.operation("delete", "/kubeflow/notebooks/:namespace/:name/debug-pod", "Delete the debug pod");
+    }
+}
+
+/// All resource types compiled into the service. New modules are added here.
+fn builtin_modules() -> Vec<Box<dyn ResourceModule>> {
+    vec![Box::new(CnpgModule), Box::new(KubeflowModule)]
+}
+
+/// The resource types enabled for this instance.
+pub struct Registry {
+    modules: Vec<Box<dyn ResourceModule>>,
+}
+
+impl Registry {
+    /// Keeps the built-in modules named in `enabled`, or all of them when `enabled` is `None`.
+    pub fn new(enabled: Option<&[String]>) -> Result<Self> {
+        let mut modules = builtin_modules();
+
+        if let Some(enabled) = enabled {
+            for name in enabled {
+                if !modules.iter().any(|module| module.name() == name) {
+                    let known: Vec<&str> = modules.iter().map(|module| module.name()).collect();
+                    return Err(AppError::Config(format!(
+                        "Unknown resource type '{}' in ENABLED_RESOURCES (known: {})",
+                        name,
+                        known.join(", ")
+                    )));
+                }
+            }
+            modules.retain(|module| enabled.iter().any(|name| name == module.name()));
+        }
+
+        Ok(Self { modules })
+    }
+
+    pub fn modules(&self) -> &[Box<dyn ResourceModule>] {
+        &self.modules
+    }
+
+    /// The enabled module called `name`, or 404.
+    pub fn module(&self, name: &str) -> Result<&dyn ResourceModule> {
+        self.modules
+            .iter()
+            .find(|module| module.name() == name)
+            .map(Box::as_ref)
+            .ok_or_else(|| AppError::NotFound(format!("Unknown or disabled resource type '{}'", name)))
+    }
+
+    /// OpenAPI description of every enabled module.
+    pub fn document(&self, doc: &mut ApiDoc) {
+        for module in &self.modules {
+            module.document(doc);
+        }
+    }
+
+    /// Routes of every enabled module.
+    pub fn router(&self) -> Router {
+        self.modules
+            .iter()
+            .fold(Router::new(), |router, module| router.merge(module.routes()))
+    }
+}
+
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+/// Builds the registry from the configuration. Call once at startup, after `config::init`.
+pub fn init(enabled: Option<&[String]>) -> Result<&'static Registry> {
+    let registry = Registry::new(enabled)?;
+    Ok(REGISTRY.get_or_init(|| registry))
+}
+
+/// Returns the active registry, or one with every built-in module if `init` has not run.
+pub fn get() -> &'static Registry {
+    REGISTRY.get_or_init(|| Registry {
+        modules: builtin_modules(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(registry: &Registry) -> Vec<&'static str> {
+        registry.modules().iter().map(|module| module.name()).collect()
+    }
+
+    #[test]
+    fn test_registry_enabled_modules() {
+        assert_eq!(names(&Registry::new(None).unwrap()), vec!["cnpg", "kubeflow"]);
+        assert_eq!(names(&Registry::new(Some(&["kubeflow".to_string()])).unwrap()), vec!["kubeflow"]);
+        assert!(Registry::new(Some(&["kubevirt".to_string()])).is_err());
+
+        let registry = Registry::new(Some(&["kubeflow".to_string()])).unwrap();
+        assert!(registry.module("kubeflow").is_ok());
+        assert!(matches!(registry.module("cnpg"), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_module_describe() {
+        let description = CnpgModule.describe();
+        assert_eq!(description["kind"], "Cluster");
+        assert_eq!(description["api_version"], "postgresql.cnpg.io/v1");
+        assert_eq!(CnpgModule.plural(), "clusters");
+    }
+
+    #[tokio::test]
+    async fn test_legacy_cluster_routes() {
+        use axum::body::Body;
+        use axum::http::{Method, Request, StatusCode};
+        use tower::ServiceExt;
+
+        // PATCH is not routed anywhere, so 405 shows the path exists without calling a handler
+        let status = |path: &'static str| async move {
+            let request = Request::builder().method(Method::PATCH).uri(path).body(Body::empty()).unwrap();
+            CnpgModule.routes().oneshot(request).await.unwrap().status()
+        };
+        for path in ["/cnpg/clusters", "/clusters", "/cnpg/clusters/team-a/db", "/clusters/team-a/db"] {
+            assert_eq!(status(path).await, StatusCode::METHOD_NOT_ALLOWED, "{}", path);
+        }
+        assert_eq!(status("/clusters/team-a/db/scale").await, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_modules_document_their_routes() {
+        let mut doc = ApiDoc::new();
+        Registry::new(None).unwrap().document(&mut doc);
+        let spec = doc.into_spec("");
+
+        for module in builtin_modules() {
+            assert!(spec["paths"][module.path()]["post"]["requestBody"].is_object(), "{}", module.name());
+            assert!(spec["paths"][format!("{}/{{namespace}}/{{name}}", module.path())]["get"].is_object());
+        }
+        assert!(spec["components"]["schemas"]["CreateNotebookRequest"].is_object());
+    }
+}
@@ -0,0 +1,144 @@
+use crate::config;
+use crate::error::{AppError, Result};
+use crate::models::cnpg::{ClusterReference, CreateDatabaseRequest, Database, DatabaseSpec};
+use crate::resources::cnpg::CnpgManager;
+use crate::resources::{set_list_age, set_list_type_meta, ListOptions, ResourceManager};
+use k8s_openapi::chrono::Utc;
+use kube::api::{DeleteParams, PostParams};
+use kube::{Api, Client};
+use serde_json::{json, Value};
+
+/// Declarative databases (`Database` objects) inside existing CNPG clusters.
+pub struct CnpgDatabaseManager;
+
+impl CnpgDatabaseManager {
+    /// Creates a `Database` object; the operator then creates the database in the referenced cluster.
+    pub async fn create(&self, client: Client, request: CreateDatabaseRequest) -> Result<Value> {
+        let namespace = request.namespace.as_deref().unwrap_or(&config::get().default_namespace);
+
+        // The operator would otherwise accept the object and only report the missing cluster in its status
+        match CnpgManager.get(client.clone(), namespace, &request.cluster).await {
+            Ok(_) => {}
+            Err(AppError::NotFound(_)) => {
+                return Err(AppError::Validation(format!(
+                    "CNPG cluster '{}' does not exist in namespace '{}'",
+                    request.cluster, namespace
+                )));
+            }
+            Err(e) => return Err(e),
+        }
+
+        let spec = DatabaseSpec {
+            cluster: ClusterReference {
+                name: request.cluster.clone(),
+            },
+            name: request.database_name.unwrap_or_else(|| request.name.clone()),
+            owner: request.owner,
+            database_reclaim_policy: request.reclaim_policy,
+            encoding: request.encoding,
+            template: request.template,
+        };
+        let database = Database::new(&request.name, spec);
+
+        let databases: Api<Database> = Api::namespaced(client, namespace);
+        let created = databases.create(&PostParams::default(), &database).await?;
+
+        Ok(json!({
+            "message": "CNPG database created successfully",
+            "name": created.metadata.name,
+            "namespace": created.metadata.namespace,
+            "cluster": request.cluster,
+            "database_name": created.spec.name,
+            "resource_type": "cnpg-database"
+        }))
+    }
+
+    pub async fn get(&self, client: Client, namespace: &str, name: &str) -> Result<Database> {
+        let databases: Api<Database> = Api::namespaced(client, namespace);
+
+        match databases.get(name).await {
+            Ok(database) => Ok(database),
+            Err(kube::Error::Api(err)) if err.code == 404 => Err(AppError::NotFound(format!(
+                "CNPG database '{}' not found in namespace '{}'",
+                name, namespace
+            ))),
+            Err(e) => Err(AppError::Kube(e)),
+        }
+    }
+
+    pub async fn list(&self, client: Client, namespace: &str, options: &ListOptions) -> Result<Value> {
+        let databases: Api<Database> = Api::namespaced(client, namespace);
+        let database_list = databases.list(&options.list_params()).await?;
+
+        let databases_info: Vec<Value> = database_list
+            .items
+            .iter()
+            .filter(|database| options.created_within(database.metadata.creation_timestamp.as_ref()))
+            .map(|database| {
+                json!({
+                    "name": database.metadata.name,
+                    "namespace": database.metadata.namespace,
+                    "cluster": database.spec.cluster.name,
+                    "database_name": database.spec.name,
+                    "owner": database.spec.owner,
+                    "applied": database.status.as_ref().and_then(|status| status.applied),
+                    "creation_timestamp": database.metadata.creation_timestamp,
+                    "resource_type": "cnpg-database"
+                })
+            })
+            .collect();
+
+        let mut list = json!({
+            "resources": databases_info,
+            "count": databases_info.len(),
+            "resource_type": "cnpg-databases",
+            "continue": database_list.metadata.continue_,
+            "remaining_item_count": database_list.metadata.remaining_item_count
+        });
+        set_list_type_meta::<Database>(&mut list);
+        set_list_age(&mut list, Utc::now());
+        Ok(list)
+    }
+
+    /// Deletes the `Database` object. Whether the database is dropped depends on its reclaim policy.
+    pub async fn delete(&self, client: Client, namespace: &str, name: &str, params: &DeleteParams) -> Result<Value> {
+        let databases: Api<Database> = Api::namespaced(client, namespace);
+
+        match databases.delete(name, params).await {
+            Ok(_) => Ok(json!({
+                "message": format!("CNPG database '{}' deleted successfully", name),
+                "resource_type": "cnpg-database"
+            })),
+            Err(kube::Error::Api(err)) if err.code == 404 => Err(AppError::NotFound(format!(
+                "CNPG database '{}' not found in namespace '{}'",
+                name, namespace
+            ))),
+            Err(e) => Err(AppError::Kube(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_serializes_to_cnpg_schema() {
+        let database = Database::new(
+            "app-db",
+            DatabaseSpec {
+                cluster: ClusterReference { name: "db".to_string() },
+                name: "app".to_string(),
+                owner: "app".to_string(),
+                database_reclaim_policy: Some("retain".to_string()),
+                encoding: None,
+                template: None,
+            },
+        );
+
+        assert_eq!(
+            serde_json::to_value(&database).unwrap()["spec"],
+            json!({ "cluster": { "name": "db" }, "name": "app", "owner": "app", "databaseReclaimPolicy": "retain" })
+        );
+    }
+}
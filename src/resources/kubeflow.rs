@@ -1,44 +1,121 @@
+use crate::config;
 use crate::error::{AppError, Result};
 use crate::models::kubeflow::{
     CreateNotebookRequest, Notebook, NotebookContainer, NotebookEnvVar, NotebookPodSpec,
     NotebookPort, NotebookPvcSource, NotebookResources, NotebookSpec, NotebookTemplate,
-    NotebookVolume, NotebookVolumeMount, UpdateNotebookRequest,
+    NotebookToleration, NotebookVolume, NotebookVolumeMount, UpdateNotebookRequest, VolumeSnapshot,
+    VolumeSnapshotSource, VolumeSnapshotSpec,
 };
-use crate::resources::ResourceManager;
+use crate::resources::quarantine::{LIFECYCLE_LABEL, QUARANTINED};
+use crate::resources::{
+    ensure_spec_matches, is_already_exists, set_list_age, set_list_type_meta, ListOptions, ResourceManager, WriteOptions,
+};
+use crate::utils::{events, namespace, quantity, warnings};
 use async_trait::async_trait;
-use k8s_openapi::api::core::v1::PersistentVolumeClaim;
+use k8s_openapi::api::core::v1::{PersistentVolumeClaim, Pod};
+use k8s_openapi::api::storage::v1::StorageClass;
+use k8s_openapi::chrono::{self, DateTime, Utc};
 use kube::{
-    api::{Api, ListParams, Patch, PatchParams},
-    Client,
+    api::{Api, DeleteParams, ListParams, Patch, PatchParams, PostParams},
+    Client, Resource, ResourceExt,
 };
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
 
 pub struct KubeflowManager;
 
+const DEFAULT_NOTEBOOK_IMAGE: &str = "kubeflownotebookswg/jupyter-scipy:v1.8.0";
+/// Annotation that tells the notebook culler to leave the notebook running when idle.
+const NO_CULLING_ANNOTATION: &str = "notebooks.kubeflow.org/no-culling";
+/// Last time the culler saw kernel activity, maintained by the notebook controller.
+const LAST_ACTIVITY_ANNOTATION: &str = "notebooks.kubeflow.org/last-activity";
+/// When the notebook was last restarted through this service.
+const RESTARTED_AT_ANNOTATION: &str = "k8s-resource-manager/restarted-at";
+/// While set, the notebook controller scales the notebook's StatefulSet to zero.
+pub const STOPPED_ANNOTATION: &str = "kubeflow-resource-stopped";
+/// Label the notebook controller puts on the pod of a notebook.
+const NOTEBOOK_NAME_LABEL: &str = "notebook-name";
+
 #[async_trait]
 impl ResourceManager for KubeflowManager {
     type CreateRequest = CreateNotebookRequest;
     type UpdateRequest = UpdateNotebookRequest;
     type Resource = Notebook;
 
-    async fn create(&self, client: Client, request: Self::CreateRequest) -> Result<Value> {
-        let namespace = request.namespace.as_deref().unwrap_or("default");
+    fn defaults(&self, request: &mut Self::CreateRequest) {
+        request
+            .image
+            .get_or_insert_with(|| DEFAULT_NOTEBOOK_IMAGE.to_string());
+
+        if request.create_workspace == Some(true) && request.existing_workspace_pvc.is_none() {
+            request
+                .workspace_volume_size
+                .get_or_insert_with(|| config::get().default_workspace_size.clone());
+        }
+    }
+
+    async fn create(&self, client: Client, mut request: Self::CreateRequest, options: &WriteOptions) -> Result<Value> {
+        self.defaults(&mut request);
+        let namespace = request.namespace.as_deref().unwrap_or(&config::get().default_namespace);
         let api: Api<Notebook> = Api::namespaced(client.clone(), namespace);
 
-        // Create PVC if workspace volume is requested
-        if let Some(volume_size) = &request.workspace_volume_size {
-            self.create_workspace_pvc(&client, namespace, &request.name, volume_size)
-                .await?;
+        // Reuse an existing claim, or create a PVC if a workspace volume is requested
+        let mut workspace_pvc = None;
+        if let Some(existing_pvc) = &request.existing_workspace_pvc {
+            self.ensure_pvc_exists(&client, namespace, existing_pvc).await?;
+        } else if let Some(volume_size) = &request.workspace_volume_size {
+            workspace_pvc = self
+                .create_workspace_pvc(&client, namespace, &request, volume_size, options)
+                .await?
+                .map(|pvc_name| json!({ "name": pvc_name, "size": volume_size }));
         }
 
         // Build notebook spec
         let notebook_spec = self.build_notebook_spec(&request)?;
 
-        let notebook = Notebook::new(&request.name, notebook_spec);
+        // Labels from the request win over those propagated from the namespace
+        let mut labels = namespace::propagated_labels(&client, namespace).await?;
+        labels.extend(request.labels.clone().into_iter().flatten());
+
+        let mut notebook = Notebook::new(&request.name, notebook_spec);
+        notebook.metadata.labels = (!labels.is_empty()).then_some(labels);
+        notebook.metadata.annotations = self.notebook_annotations(&request);
+
+        match api.create(&options.post_params(), &notebook).await {
+            Ok(created) => {
+                if !options.dry_run {
+                    events::publish(
+                        &client,
+                        created.object_ref(&()),
+                        events::normal("Created", "Create", format!("Created notebook {}", request.name)),
+                    );
+                }
+                let mut response = serde_json::to_value(created)?;
+                if options.if_not_exists {
+                    response["created"] = Value::Bool(true);
+                }
+                if let Some(workspace_pvc) = workspace_pvc {
+                    response["workspace_pvc"] = workspace_pvc;
+                }
+                Ok(response)
+            }
+            Err(e) if options.if_not_exists && is_already_exists(&e) => {
+                let existing = self.get(client, namespace, &request.name).await?;
+                ensure_spec_matches(
+                    "Kubeflow notebook",
+                    &request.name,
+                    &json!({ "spec": existing.spec }),
+                    &json!({ "spec": notebook.spec }),
+                )?;
 
-        match api.create(&Default::default(), &notebook).await {
-            Ok(created) => Ok(serde_json::to_value(created)?),
+                let mut response = serde_json::to_value(existing)?;
+                response["created"] = Value::Bool(false);
+                if let Some(workspace_pvc) = workspace_pvc {
+                    response["workspace_pvc"] = workspace_pvc;
+                }
+                Ok(response)
+            }
             Err(e) => Err(AppError::Kube(e)),
         }
     }
@@ -52,11 +129,22 @@ impl ResourceManager for KubeflowManager {
         }
     }
 
-    async fn list(&self, client: Client, namespace: &str) -> Result<Value> {
+    async fn list(&self, client: Client, namespace: &str, options: &ListOptions) -> Result<Value> {
         let api: Api<Notebook> = Api::namespaced(client, namespace);
 
-        match api.list(&ListParams::default()).await {
-            Ok(notebooks) => Ok(serde_json::to_value(notebooks)?),
+        match api.list(&options.list_params()).await {
+            Ok(mut notebooks) => {
+                notebooks
+                    .items
+                    .retain(|notebook| options.created_within(notebook.metadata.creation_timestamp.as_ref()));
+                let mut list = serde_json::to_value(notebooks)?;
+                set_list_type_meta::<Notebook>(&mut list);
+                set_list_age(&mut list, Utc::now());
+                for item in list["items"].as_array_mut().into_iter().flatten() {
+                    set_cull_countdown(item, Utc::now(), config::get().notebook_cull_idle_time);
+                }
+                Ok(list)
+            }
             Err(e) => Err(AppError::Kube(e)),
         }
     }
@@ -67,35 +155,53 @@ impl ResourceManager for KubeflowManager {
         namespace: &str,
         name: &str,
         request: Self::UpdateRequest,
+        options: &WriteOptions,
     ) -> Result<Value> {
         let api: Api<Notebook> = Api::namespaced(client.clone(), namespace);
 
         // Get existing notebook
-        let existing = self.get(client, namespace, name).await?;
+        let existing = self.get(client.clone(), namespace, name).await?;
         
         // Build updated spec
         let updated_spec = self.build_update_spec(&existing.spec, &request)?;
         
         let patch = json!({
+            "apiVersion": Notebook::api_version(&()),
+            "kind": Notebook::kind(&()),
+            "metadata": { "name": name },
             "spec": updated_spec
         });
 
         match api
-            .patch(name, &PatchParams::default(), &Patch::Merge(patch))
+            .patch(name, &options.apply_params(), &Patch::Apply(patch))
             .await
         {
-            Ok(updated) => Ok(serde_json::to_value(updated)?),
+            Ok(updated) => {
+                if !options.dry_run {
+                    events::publish(
+                        &client,
+                        updated.object_ref(&()),
+                        events::normal("Updated", "Update", format!("Updated notebook {}", name)),
+                    );
+                }
+                Ok(serde_json::to_value(updated)?)
+            }
+            // Another field manager (e.g. kubectl or a GitOps controller) owns a field this update changes
+            Err(kube::Error::Api(err)) if err.code == 409 => Err(AppError::Conflict(
+                format!("{}; retry with force_ownership=true to take ownership of these fields", err.message),
+                Value::Null,
+            )),
             Err(e) => Err(AppError::Kube(e)),
         }
     }
 
-    async fn delete(&self, client: Client, namespace: &str, name: &str) -> Result<Value> {
+    async fn delete(&self, client: Client, namespace: &str, name: &str, params: &DeleteParams) -> Result<Value> {
         let api: Api<Notebook> = Api::namespaced(client.clone(), namespace);
 
-        match api.delete(name, &Default::default()).await {
+        match api.delete(name, params).await {
             Ok(_result) => {
                 // Also delete the workspace PVC if it exists
-                let _ = self.delete_workspace_pvc(&client, namespace, name).await;
+                let _ = self.delete_workspace_pvc(&client, namespace, name, params).await;
                 Ok(serde_json::json!({
                     "status": "deleted",
                     "name": name,
@@ -133,10 +239,13 @@ impl KubeflowManager {
             limits.insert("nvidia.com/gpu".to_string(), gpu_limit.clone());
         }
 
-        let notebook_resources = if !requests.is_empty() || !limits.is_empty() {
+        let claims = request.resource_claims.clone().filter(|claims| !claims.is_empty());
+
+        let notebook_resources = if !requests.is_empty() || !limits.is_empty() || claims.is_some() {
             Some(NotebookResources {
                 requests: if requests.is_empty() { None } else { Some(requests) },
                 limits: if limits.is_empty() { None } else { Some(limits) },
+                claims,
             })
         } else {
             None
@@ -154,7 +263,9 @@ impl KubeflowManager {
         });
 
         // Build volume mounts and volumes
-        let (volume_mounts, volumes) = if request.workspace_volume_size.is_some() {
+        let (volume_mounts, volumes) = if request.workspace_volume_size.is_some()
+            || request.existing_workspace_pvc.is_some()
+        {
             let mount_path = request
                 .workspace_volume_mount
                 .as_deref()
@@ -168,7 +279,10 @@ impl KubeflowManager {
             let volumes = vec![NotebookVolume {
                 name: format!("{}-workspace", request.name),
                 persistent_volume_claim: Some(NotebookPvcSource {
-                    claim_name: format!("{}-workspace-pvc", request.name),
+                    claim_name: request
+                        .existing_workspace_pvc
+                        .clone()
+                        .unwrap_or_else(|| format!("{}-workspace-pvc", request.name)),
                 }),
                 empty_dir: None,
             }];
@@ -178,6 +292,10 @@ impl KubeflowManager {
             (None, None)
         };
 
+        if let Some(ref mounts) = volume_mounts {
+            check_mount_paths(mounts)?;
+        }
+
         // Build ports
         let ports = vec![NotebookPort {
             container_port: 8888,
@@ -187,7 +305,13 @@ impl KubeflowManager {
 
         let container = NotebookContainer {
             name: "notebook".to_string(),
-            image: request.image.clone(),
+            image: request
+                .image
+                .clone()
+                .unwrap_or_else(|| DEFAULT_NOTEBOOK_IMAGE.to_string()),
+            image_pull_policy: request.image_pull_policy.clone(),
+            command: request.command.clone().filter(|command| !command.is_empty()),
+            args: request.args.clone().filter(|args| !args.is_empty()),
             resources: notebook_resources,
             env: env_vars,
             volume_mounts,
@@ -197,7 +321,16 @@ impl KubeflowManager {
         let pod_spec = NotebookPodSpec {
             containers: vec![container],
             volumes,
-            service_account_name: request.service_account.clone(),
+            service_account_name: request
+                .service_account
+                .clone()
+                .or_else(|| config::get().default_notebook_service_account.clone()),
+            node_selector: request
+                .node_selector
+                .clone()
+                .filter(|selector| !selector.is_empty())
+                .map(|selector| selector.into_iter().collect()),
+            tolerations: self.notebook_tolerations(request),
         };
 
         let template = NotebookTemplate { spec: pod_spec };
@@ -205,6 +338,25 @@ impl KubeflowManager {
         Ok(NotebookSpec { template })
     }
 
+    /// The requested tolerations, plus the GPU node toleration for GPU notebooks unless one
+    /// already covers the taint or the caller opted out.
+    fn notebook_tolerations(&self, request: &CreateNotebookRequest) -> Option<Vec<NotebookToleration>> {
+        let mut tolerations = request.tolerations.clone().unwrap_or_default();
+
+        let gpu_key = &config::get().gpu_toleration_key;
+        let wants_gpu_toleration = request.gpu_limit.is_some() && request.auto_gpu_toleration != Some(false);
+        if wants_gpu_toleration && !tolerations.iter().any(|toleration| tolerates_no_schedule(toleration, gpu_key)) {
+            tolerations.push(NotebookToleration {
+                key: Some(gpu_key.clone()),
+                operator: Some("Exists".to_string()),
+                effect: Some("NoSchedule".to_string()),
+                ..Default::default()
+            });
+        }
+
+        if tolerations.is_empty() { None } else { Some(tolerations) }
+    }
+
     fn build_update_spec(
         &self,
         existing_spec: &NotebookSpec,
@@ -218,6 +370,19 @@ impl KubeflowManager {
                 container.image = image.clone();
             }
 
+            // Set or clear the pull policy if provided
+            if let Some(image_pull_policy) = &request.image_pull_policy {
+                container.image_pull_policy = image_pull_policy.clone();
+            }
+
+            // Replace or clear the command and arguments if provided
+            if let Some(command) = &request.command {
+                container.command = Some(command.clone()).filter(|command| !command.is_empty());
+            }
+            if let Some(args) = &request.args {
+                container.args = Some(args.clone()).filter(|args| !args.is_empty());
+            }
+
             // Update resources if provided
             if request.cpu_request.is_some()
                 || request.cpu_limit.is_some()
@@ -255,6 +420,7 @@ impl KubeflowManager {
                 container.resources = Some(NotebookResources {
                     requests: if requests.is_empty() { None } else { Some(requests) },
                     limits: if limits.is_empty() { None } else { Some(limits) },
+                    claims: container.resources.as_ref().and_then(|r| r.claims.clone()),
                 });
             }
 
@@ -275,25 +441,50 @@ impl KubeflowManager {
         Ok(updated_spec)
     }
 
+    fn notebook_annotations(&self, request: &CreateNotebookRequest) -> Option<BTreeMap<String, String>> {
+        let mut annotations: BTreeMap<String, String> = request.annotations.clone().into_iter().flatten().collect();
+        if request.disable_culling == Some(true) {
+            annotations.insert(NO_CULLING_ANNOTATION.to_string(), "true".to_string());
+        }
+        (!annotations.is_empty()).then_some(annotations)
+    }
+
+    /// Labels for the workspace PVC: the notebook's own labels plus `notebook=<name>`.
+    fn workspace_pvc_labels(&self, request: &CreateNotebookRequest) -> BTreeMap<String, String> {
+        let mut labels: BTreeMap<String, String> = request
+            .labels
+            .as_ref()
+            .map(|labels| labels.clone().into_iter().collect())
+            .unwrap_or_default();
+        labels.insert("notebook".to_string(), request.name.clone());
+        labels
+    }
+
+    /// Creates the notebook's workspace PVC and returns its name, or `None` if it could not be created.
     async fn create_workspace_pvc(
         &self,
         client: &Client,
         namespace: &str,
-        notebook_name: &str,
+        request: &CreateNotebookRequest,
         size: &str,
-    ) -> Result<()> {
+        options: &WriteOptions,
+    ) -> Result<Option<String>> {
         let pvc_api: Api<PersistentVolumeClaim> = Api::namespaced(client.clone(), namespace);
-        let pvc_name = format!("{}-workspace-pvc", notebook_name);
+        let pvc_name = format!("{}-workspace-pvc", request.name);
+        let labels = self.workspace_pvc_labels(request);
+        let storage_class = request.workspace_storage_class.as_deref();
 
         let pvc = serde_json::from_value::<PersistentVolumeClaim>(json!({
             "apiVersion": "v1",
             "kind": "PersistentVolumeClaim",
             "metadata": {
                 "name": pvc_name,
-                "namespace": namespace
+                "namespace": namespace,
+                "labels": labels
             },
             "spec": {
                 "accessModes": ["ReadWriteOnce"],
+                "storageClassName": storage_class,
                 "resources": {
                     "requests": {
                         "storage": size
@@ -302,27 +493,885 @@ impl KubeflowManager {
             }
         })).map_err(|e| AppError::Internal(format!("Failed to create PVC spec: {}", e)))?;
 
-        match pvc_api.create(&Default::default(), &pvc).await {
-            Ok(_) => Ok(()),
+        match pvc_api.create(&options.post_params(), &pvc).await {
+            Ok(_) => Ok(Some(pvc_name)),
+            // Retried creates find the workspace from the first attempt
+            Err(e) if options.if_not_exists && is_already_exists(&e) => Ok(Some(pvc_name)),
             Err(e) => {
                 tracing::warn!("Failed to create workspace PVC: {}", e);
-                Ok(()) // Don't fail notebook creation if PVC creation fails
+                events::publish(
+                    client,
+                    events::reference::<Notebook>(namespace, &request.name),
+                    events::warning(
+                        "WorkspacePVCFailed",
+                        "Create",
+                        format!("Failed to create workspace PVC {}: {}", pvc_name, e),
+                    ),
+                );
+                Ok(None) // Don't fail notebook creation if PVC creation fails
             }
         }
     }
 
+    async fn ensure_pvc_exists(&self, client: &Client, namespace: &str, pvc_name: &str) -> Result<()> {
+        let pvc_api: Api<PersistentVolumeClaim> = Api::namespaced(client.clone(), namespace);
+
+        match pvc_api.get_opt(pvc_name).await? {
+            Some(_) => Ok(()),
+            None => Err(AppError::NotFound(format!(
+                "PersistentVolumeClaim '{}' not found in namespace '{}'",
+                pvc_name, namespace
+            ))),
+        }
+    }
+
     async fn delete_workspace_pvc(
         &self,
         client: &Client,
         namespace: &str,
         notebook_name: &str,
+        params: &DeleteParams,
     ) -> Result<()> {
         let pvc_api: Api<PersistentVolumeClaim> = Api::namespaced(client.clone(), namespace);
         let pvc_name = format!("{}-workspace-pvc", notebook_name);
 
-        match pvc_api.delete(&pvc_name, &Default::default()).await {
+        match pvc_api.delete(&pvc_name, params).await {
             Ok(_) => Ok(()),
             Err(_) => Ok(()), // PVC might not exist, that's okay
         }
     }
-}
\ No newline at end of file
+}
+
+impl KubeflowManager {
+    /// Snapshots the notebook's workspace PVC with a CSI `VolumeSnapshot`.
+    ///
+    /// The snapshot is labelled `notebook=<name>` so `list_workspace_snapshots` can find it.
+    pub async fn create_workspace_snapshot(
+        &self,
+        client: Client,
+        namespace: &str,
+        name: &str,
+        snapshot_name: Option<String>,
+        snapshot_class: Option<String>,
+    ) -> Result<Value> {
+        let notebook = self.get(client.clone(), namespace, name).await?;
+        let pvc_name = workspace_pvc_name(&notebook, name).ok_or_else(|| {
+            AppError::BadRequest(format!("Kubeflow notebook '{}' has no workspace PVC to snapshot", name))
+        })?;
+        self.ensure_pvc_exists(&client, namespace, &pvc_name).await?;
+
+        let snapshot_name =
+            snapshot_name.unwrap_or_else(|| format!("{}-{}", name, Utc::now().format("%Y%m%d-%H%M%S")));
+        let mut snapshot = VolumeSnapshot::new(
+            &snapshot_name,
+            VolumeSnapshotSpec {
+                volume_snapshot_class_name: snapshot_class.or_else(|| config::get().volume_snapshot_class.clone()),
+                source: VolumeSnapshotSource {
+                    persistent_volume_claim_name: Some(pvc_name.clone()),
+                    volume_snapshot_content_name: None,
+                },
+            },
+        );
+        snapshot.metadata.labels = Some(BTreeMap::from([("notebook".to_string(), name.to_string())]));
+
+        let api: Api<VolumeSnapshot> = Api::namespaced(client, namespace);
+        let created = api
+            .create(&PostParams::default(), &snapshot)
+            .await
+            .map_err(snapshot_api_error)?;
+
+        tracing::info!(notebook_name = name, namespace = namespace, snapshot = %snapshot_name, pvc = %pvc_name, "Workspace snapshot requested");
+
+        Ok(json!({
+            "message": "Workspace snapshot created",
+            "name": name,
+            "namespace": namespace,
+            "resource_type": "kubeflow-notebook",
+            "snapshot": snapshot_summary(&created)
+        }))
+    }
+
+    /// Lists the workspace snapshots taken of a notebook, oldest first.
+    pub async fn list_workspace_snapshots(&self, client: Client, namespace: &str, name: &str) -> Result<Value> {
+        let api: Api<VolumeSnapshot> = Api::namespaced(client, namespace);
+        let mut snapshots = api
+            .list(&ListParams::default().labels(&format!("notebook={}", name)))
+            .await
+            .map_err(snapshot_api_error)?
+            .items;
+        snapshots.sort_by(|a, b| a.metadata.creation_timestamp.cmp(&b.metadata.creation_timestamp));
+
+        let snapshots: Vec<Value> = snapshots.iter().map(snapshot_summary).collect();
+        Ok(json!({
+            "name": name,
+            "namespace": namespace,
+            "snapshots": snapshots,
+            "count": snapshots.len()
+        }))
+    }
+}
+
+impl KubeflowManager {
+    /// Restarts the notebook server by deleting its pod, which the notebook's StatefulSet recreates
+    /// with the same spec.
+    ///
+    /// The notebook is annotated with the restart time, and its last activity is reset so the culler
+    /// does not stop it right after the restart.
+    pub async fn restart(&self, client: Client, namespace: &str, name: &str) -> Result<Value> {
+        self.get(client.clone(), namespace, name).await?;
+
+        let now = Utc::now().to_rfc3339();
+        let patch = json!({
+            "metadata": {
+                "annotations": {
+                    RESTARTED_AT_ANNOTATION: now,
+                    LAST_ACTIVITY_ANNOTATION: now
+                }
+            }
+        });
+        let api: Api<Notebook> = Api::namespaced(client.clone(), namespace);
+        let notebook = api.patch(name, &PatchParams::default(), &Patch::Merge(&patch)).await?;
+
+        // The notebook controller runs the server as the only replica of a StatefulSet named after the notebook
+        let pod_name = format!("{}-0", name);
+        let pods: Api<Pod> = Api::namespaced(client.clone(), namespace);
+        let pod = match pods.delete(&pod_name, &DeleteParams::default()).await {
+            Ok(_) => Some(pod_name),
+            // A stopped notebook has no pod; it starts fresh when resumed
+            Err(kube::Error::Api(err)) if err.code == 404 => None,
+            Err(e) => return Err(AppError::Kube(e)),
+        };
+
+        events::publish(
+            &client,
+            notebook.object_ref(&()),
+            events::normal("Restarted", "Restart", format!("Restarted notebook {}", name)),
+        );
+        tracing::info!(notebook_name = name, namespace = namespace, pod = ?pod, "Notebook restarted");
+
+        Ok(json!({
+            "message": if pod.is_some() { "Notebook restart requested" } else { "Notebook has no running pod to restart" },
+            "name": name,
+            "namespace": namespace,
+            "resource_type": "kubeflow-notebook",
+            "restarted_pod": pod,
+            "notebook": notebook
+        }))
+    }
+
+    /// Suspends the notebook: the controller removes its pod but keeps the notebook and its workspace.
+    pub async fn stop(&self, client: Client, namespace: &str, name: &str) -> Result<Value> {
+        let notebook = self.get(client.clone(), namespace, name).await?;
+        if let Some(stopped_at) = notebook.annotations().get(STOPPED_ANNOTATION) {
+            return Ok(stop_response(name, namespace, "Notebook is already stopped", Some(stopped_at)));
+        }
+
+        let stopped_at = Utc::now().to_rfc3339();
+        let patch = json!({ "metadata": { "annotations": { STOPPED_ANNOTATION: stopped_at } } });
+        let api: Api<Notebook> = Api::namespaced(client.clone(), namespace);
+        let notebook = api.patch(name, &PatchParams::default(), &Patch::Merge(&patch)).await?;
+
+        events::publish(
+            &client,
+            notebook.object_ref(&()),
+            events::normal("Stopped", "Stop", format!("Stopped notebook {}", name)),
+        );
+        tracing::info!(notebook_name = name, namespace = namespace, "Notebook stopped");
+
+        Ok(stop_response(name, namespace, "Notebook stopped", Some(&stopped_at)))
+    }
+
+    /// Resumes a stopped notebook; the controller recreates its pod.
+    ///
+    /// Quarantined notebooks stay stopped until they are restored.
+    pub async fn start(&self, client: Client, namespace: &str, name: &str) -> Result<Value> {
+        let notebook = self.get(client.clone(), namespace, name).await?;
+        if notebook.labels().get(LIFECYCLE_LABEL).map(String::as_str) == Some(QUARANTINED) {
+            return Err(AppError::Conflict(
+                format!("Kubeflow notebook '{}' is quarantined; restore it instead of starting it", name),
+                json!({ "name": name, "namespace": namespace }),
+            ));
+        }
+        if !notebook.annotations().contains_key(STOPPED_ANNOTATION) {
+            return Ok(stop_response(name, namespace, "Notebook is already running", None));
+        }
+
+        let patch = json!({ "metadata": { "annotations": { STOPPED_ANNOTATION: null } } });
+        let api: Api<Notebook> = Api::namespaced(client.clone(), namespace);
+        let notebook = api.patch(name, &PatchParams::default(), &Patch::Merge(&patch)).await?;
+
+        events::publish(
+            &client,
+            notebook.object_ref(&()),
+            events::normal("Started", "Start", format!("Started notebook {}", name)),
+        );
+        tracing::info!(notebook_name = name, namespace = namespace, "Notebook started");
+
+        Ok(stop_response(name, namespace, "Notebook started", None))
+    }
+
+    /// Grows the notebook's workspace PVC to `size` by patching its storage request.
+    ///
+    /// Relies on the storage class allowing volume expansion; shrinking is rejected.
+    pub async fn resize_workspace(&self, client: Client, namespace: &str, name: &str, size: &str) -> Result<Value> {
+        let notebook = self.get(client.clone(), namespace, name).await?;
+        let pvc_name = workspace_pvc_name(&notebook, name).ok_or_else(|| {
+            AppError::BadRequest(format!("Kubeflow notebook '{}' has no workspace PVC to resize", name))
+        })?;
+
+        let pvc_api: Api<PersistentVolumeClaim> = Api::namespaced(client.clone(), namespace);
+        let pvc = pvc_api.get_opt(&pvc_name).await?.ok_or_else(|| {
+            AppError::NotFound(format!("PersistentVolumeClaim '{}' not found in namespace '{}'", pvc_name, namespace))
+        })?;
+
+        let current = pvc
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.resources.as_ref())
+            .and_then(|resources| resources.requests.as_ref())
+            .and_then(|requests| requests.get("storage"))
+            .map(|quantity| quantity.0.clone());
+        if let Some(ref current) = current {
+            check_workspace_growth(current, size)?;
+        }
+
+        let storage_class = pvc.spec.as_ref().and_then(|spec| spec.storage_class_name.clone());
+        if let Some(ref storage_class) = storage_class {
+            let classes: Api<StorageClass> = Api::all(client);
+            let expandable = classes
+                .get_opt(storage_class)
+                .await?
+                .is_some_and(|class| class.allow_volume_expansion == Some(true));
+            if !expandable {
+                return Err(AppError::BadRequest(format!(
+                    "Storage class '{}' does not allow volume expansion; the workspace cannot be resized",
+                    storage_class
+                )));
+            }
+        }
+
+        let patch = json!({ "spec": { "resources": { "requests": { "storage": size } } } });
+        pvc_api
+            .patch(&pvc_name, &PatchParams::default(), &Patch::Merge(&patch))
+            .await
+            .map_err(|e| match e {
+                // The API server refuses to grow claims whose class or provisioner cannot expand them
+                kube::Error::Api(ref response) if response.code == 403 || response.code == 422 => {
+                    AppError::BadRequest(format!("Workspace PVC '{}' cannot be resized: {}", pvc_name, response.message))
+                }
+                e => AppError::Kube(e),
+            })?;
+
+        tracing::info!(notebook_name = name, namespace = namespace, pvc = %pvc_name, size = size, "Workspace resize requested");
+
+        Ok(json!({
+            "message": "Workspace resize requested",
+            "name": name,
+            "namespace": namespace,
+            "pvc": pvc_name,
+            "previous_size": current,
+            "size": size,
+            "storage_class": storage_class
+        }))
+    }
+
+    /// Starts a pod with a shell that mounts the notebook's workspace PVC at `/workspace`.
+    ///
+    /// The pod is named `<notebook>-debug`, so a notebook has at most one, and it stops
+    /// itself after `DEBUG_POD_DEADLINE_SECONDS`.
+    pub async fn create_debug_pod(&self, client: Client, namespace: &str, name: &str) -> Result<Value> {
+        let notebook = self.get(client.clone(), namespace, name).await?;
+        let pvc_name = workspace_pvc_name(&notebook, name).ok_or_else(|| {
+            AppError::BadRequest(format!("Kubeflow notebook '{}' has no workspace PVC to debug", name))
+        })?;
+        self.ensure_pvc_exists(&client, namespace, &pvc_name).await?;
+
+        let config = config::get();
+        let pod = debug_pod(name, namespace, &pvc_name, &config.debug_pod_image, config.debug_pod_deadline.as_secs())?;
+        let pod_name = debug_pod_name(name);
+
+        let api: Api<Pod> = Api::namespaced(client, namespace);
+        match api.create(&PostParams::default(), &pod).await {
+            Ok(_) => {}
+            Err(e) if is_already_exists(&e) => {
+                return Err(AppError::Conflict(
+                    format!("Debug pod '{}' already exists; delete it before starting a new one", pod_name),
+                    json!({ "pod": pod_name }),
+                ));
+            }
+            Err(e) => return Err(AppError::Kube(e)),
+        }
+
+        tracing::info!(notebook_name = name, namespace = namespace, pod = %pod_name, pvc = %pvc_name, "Notebook debug pod created");
+
+        Ok(json!({
+            "message": "Debug pod created",
+            "name": name,
+            "namespace": namespace,
+            "resource_type": "kubeflow-notebook",
+            "pod": pod_name,
+            "pvc": pvc_name,
+            "active_deadline_seconds": config.debug_pod_deadline.as_secs(),
+            "exec": format!("kubectl exec -it -n {} {} -- sh", namespace, pod_name)
+        }))
+    }
+
+    pub async fn delete_debug_pod(&self, client: Client, namespace: &str, name: &str) -> Result<Value> {
+        let pod_name = debug_pod_name(name);
+        let api: Api<Pod> = Api::namespaced(client, namespace);
+
+        match api.delete(&pod_name, &DeleteParams::default()).await {
+            Ok(_) => Ok(json!({
+                "message": "Debug pod deleted",
+                "name": name,
+                "namespace": namespace,
+                "pod": pod_name
+            })),
+            Err(kube::Error::Api(err)) if err.code == 404 => Err(AppError::NotFound(format!(
+                "No debug pod for Kubeflow notebook '{}' in namespace '{}'",
+                name, namespace
+            ))),
+            Err(e) => Err(AppError::Kube(e)),
+        }
+    }
+}
+
+fn stop_response(name: &str, namespace: &str, message: &str, stopped_at: Option<&String>) -> Value {
+    json!({
+        "message": message,
+        "name": name,
+        "namespace": namespace,
+        "resource_type": "kubeflow-notebook",
+        "stopped": stopped_at.is_some(),
+        "stopped_at": stopped_at
+    })
+}
+
+/// Rejects a workspace resize that would not grow the claim.
+fn check_workspace_growth(current: &str, requested: &str) -> Result<()> {
+    let (Some(current_bytes), Some(requested_bytes)) = (quantity::parse_bytes(current), quantity::parse_bytes(requested))
+    else {
+        return Ok(());
+    };
+
+    if requested_bytes <= current_bytes {
+        return Err(AppError::Validation(format!(
+            "Workspace size must be larger than the current {}; shrinking a PVC is not supported",
+            current
+        )));
+    }
+    Ok(())
+}
+
+fn debug_pod_name(notebook_name: &str) -> String {
+    format!("{}-debug", notebook_name)
+}
+
+/// A non-root, restart-free pod that sleeps until its deadline so users can `kubectl exec` into it.
+fn debug_pod(notebook_name: &str, namespace: &str, pvc_name: &str, image: &str, deadline_seconds: u64) -> Result<Pod> {
+    serde_json::from_value(json!({
+        "apiVersion": "v1",
+        "kind": "Pod",
+        "metadata": {
+            "name": debug_pod_name(notebook_name),
+            "namespace": namespace,
+            "labels": {
+                "notebook": notebook_name,
+                "app.kubernetes.io/component": "notebook-debug",
+                "app.kubernetes.io/managed-by": "k8s-resource-manager"
+            }
+        },
+        "spec": {
+            "restartPolicy": "Never",
+            "activeDeadlineSeconds": deadline_seconds,
+            "securityContext": {
+                "runAsNonRoot": true,
+                "runAsUser": 1000,
+                "runAsGroup": 100,
+                "fsGroup": 100
+            },
+            "containers": [{
+                "name": "debug",
+                "image": image,
+                "command": ["sleep", deadline_seconds.to_string()],
+                "stdin": true,
+                "tty": true,
+                "securityContext": {
+                    "allowPrivilegeEscalation": false,
+                    "capabilities": { "drop": ["ALL"] }
+                },
+                "volumeMounts": [{ "name": "workspace", "mountPath": "/workspace" }]
+            }],
+            "volumes": [{
+                "name": "workspace",
+                "persistentVolumeClaim": { "claimName": pvc_name }
+            }]
+        }
+    }))
+    .map_err(|e| AppError::Internal(format!("Failed to create debug pod spec: {}", e)))
+}
+
+/// The PVC backing the notebook's workspace volume, if it has one.
+fn workspace_pvc_name(notebook: &Notebook, name: &str) -> Option<String> {
+    let workspace_volume = format!("{}-workspace", name);
+    notebook
+        .spec
+        .template
+        .spec
+        .volumes
+        .iter()
+        .flatten()
+        .find(|volume| volume.name == workspace_volume)
+        .and_then(|volume| volume.persistent_volume_claim.as_ref())
+        .map(|claim| claim.claim_name.clone())
+}
+
+impl KubeflowManager {
+    /// Reports whether the notebook's pod is running and where the notebook is reached.
+    ///
+    /// A notebook whose pod does not exist yet (just created or stopped) is reported as `Pending`. When its
+    /// pods cannot be listed (e.g. without permission to), it is reported as `Unknown` with a response warning.
+    pub async fn pod_status(&self, client: Client, namespace: &str, name: &str) -> Value {
+        let pods: Api<Pod> = Api::namespaced(client, namespace);
+        let params = ListParams::default().labels(&format!("{}={}", NOTEBOOK_NAME_LABEL, name));
+        let (pod_phase, ready) = match pods.list(&params).await {
+            Ok(list) => pod_state(list.items.first()),
+            Err(e) => {
+                warnings::push(format!("Pod status of notebook '{}' is unknown: {}", name, e));
+                ("Unknown".to_string(), false)
+            }
+        };
+
+        json!({
+            "pod_phase": pod_phase,
+            "ready": ready,
+            "url": notebook_url(config::get().notebook_base_url.as_deref(), namespace, name)
+        })
+    }
+}
+
+/// Phase of the notebook's pod and whether it passes its readiness check.
+fn pod_state(pod: Option<&Pod>) -> (String, bool) {
+    let Some(status) = pod.and_then(|pod| pod.status.as_ref()) else {
+        return ("Pending".to_string(), false);
+    };
+    let phase = status.phase.clone().unwrap_or_else(|| "Pending".to_string());
+    let ready = status
+        .conditions
+        .iter()
+        .flatten()
+        .any(|condition| condition.type_ == "Ready" && condition.status == "True");
+    (phase, ready)
+}
+
+/// Address of the notebook behind the Kubeflow gateway, which serves it at `/notebook/<namespace>/<name>/`.
+fn notebook_url(base_url: Option<&str>, namespace: &str, name: &str) -> String {
+    format!("{}/notebook/{}/{}/", base_url.unwrap_or_default(), namespace, name)
+}
+
+/// Adds `seconds_until_cull` to a serialized notebook: the idle time left before the culler stops it.
+///
+/// Left out when culling is not configured, the notebook is exempt, or the controller has not recorded
+/// any activity yet. Zero means the notebook is due to be culled at the next idleness check.
+pub fn set_cull_countdown(notebook: &mut Value, now: DateTime<Utc>, idle_time: Option<Duration>) {
+    let Some(idle_time) = idle_time.and_then(|idle| chrono::Duration::from_std(idle).ok()) else {
+        return;
+    };
+    let annotations = notebook.pointer("/metadata/annotations");
+    if annotations.and_then(|a| a.get(NO_CULLING_ANNOTATION)).and_then(Value::as_str) == Some("true") {
+        return;
+    }
+    let Some(last_activity) = annotations
+        .and_then(|a| a.get(LAST_ACTIVITY_ANNOTATION))
+        .and_then(Value::as_str)
+        .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+    else {
+        return;
+    };
+
+    let remaining = (last_activity.with_timezone(&Utc) + idle_time - now).num_seconds().max(0);
+    if let Some(object) = notebook.as_object_mut() {
+        object.insert("seconds_until_cull".to_string(), remaining.into());
+    }
+}
+
+fn snapshot_summary(snapshot: &VolumeSnapshot) -> Value {
+    let status = snapshot.status.as_ref();
+    json!({
+        "name": snapshot.metadata.name,
+        "source_pvc": snapshot.spec.source.persistent_volume_claim_name,
+        "snapshot_class": snapshot.spec.volume_snapshot_class_name,
+        "ready_to_use": status.and_then(|s| s.ready_to_use).unwrap_or(false),
+        "restore_size": status.and_then(|s| s.restore_size.clone()),
+        "error": status.and_then(|s| s.error.as_ref()).and_then(|e| e.message.clone()),
+        "creation_timestamp": snapshot.metadata.creation_timestamp
+    })
+}
+
+/// A 404 from the snapshot API means the `VolumeSnapshot` CRD is missing, i.e. no CSI snapshotter is installed.
+fn snapshot_api_error(error: kube::Error) -> AppError {
+    match error {
+        kube::Error::Api(ref response) if response.code == 404 => AppError::NotFound(format!(
+            "The {} API is not available in this cluster; install the CSI external snapshotter to use workspace snapshots",
+            VolumeSnapshot::api_version(&())
+        )),
+        error => AppError::Kube(error),
+    }
+}
+
+/// Whether `toleration` lets a pod schedule onto nodes with a `NoSchedule` taint keyed `key`.
+fn tolerates_no_schedule(toleration: &NotebookToleration, key: &str) -> bool {
+    let key_matches = match toleration.key.as_deref() {
+        None | Some("") => toleration.operator.as_deref() == Some("Exists"),
+        Some(toleration_key) => toleration_key == key,
+    };
+    let effect_matches = matches!(toleration.effect.as_deref(), None | Some("") | Some("NoSchedule"));
+
+    key_matches && effect_matches
+}
+
+/// Rejects volume mounts that share a mount path, which the API server would only refuse when creating the pod.
+fn check_mount_paths(mounts: &[NotebookVolumeMount]) -> Result<()> {
+    let mut seen: HashMap<&str, &str> = HashMap::new();
+    let mut conflicts = Vec::new();
+
+    for mount in mounts {
+        // `/data/` and `/data` are the same directory
+        let path = match mount.mount_path.trim_end_matches('/') {
+            "" => "/",
+            path => path,
+        };
+        if let Some(other) = seen.insert(path, &mount.name) {
+            conflicts.push(format!("'{}' (volumes '{}' and '{}')", path, other, mount.name));
+        }
+    }
+
+    if conflicts.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!(
+            "Volume mount paths must be unique; duplicated: {}",
+            conflicts.join(", ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources;
+
+    fn create_request(body: Value) -> CreateNotebookRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_pod_status_without_pod_access() {
+        let (status, warnings) = warnings::scope(async {
+            let status = KubeflowManager.pod_status(resources::failing_client(403, "Forbidden"), "team-a", "nb").await;
+            (status, warnings::take())
+        })
+        .await;
+        assert_eq!(status["pod_phase"], "Unknown");
+        assert_eq!(status["ready"], false);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("Pod status of notebook 'nb' is unknown"));
+    }
+
+    #[test]
+    fn test_defaults_fill_omitted_image() {
+        let mut request = create_request(json!({ "name": "nb" }));
+        KubeflowManager.defaults(&mut request);
+        assert_eq!(request.image.as_deref(), Some(DEFAULT_NOTEBOOK_IMAGE));
+
+        let spec = KubeflowManager.build_notebook_spec(&request).unwrap();
+        assert_eq!(spec.template.spec.containers[0].image, DEFAULT_NOTEBOOK_IMAGE);
+    }
+
+    #[test]
+    fn test_node_selector_is_set() {
+        let request = create_request(json!({
+            "name": "nb",
+            "gpu_limit": "1",
+            "node_selector": { "nvidia.com/gpu.product": "NVIDIA-A100-SXM4-80GB" }
+        }));
+        let spec = KubeflowManager.build_notebook_spec(&request).unwrap();
+
+        let node_selector = spec.template.spec.node_selector.unwrap();
+        assert_eq!(node_selector["nvidia.com/gpu.product"], "NVIDIA-A100-SXM4-80GB");
+
+        let spec = KubeflowManager.build_notebook_spec(&create_request(json!({ "name": "nb" }))).unwrap();
+        assert!(spec.template.spec.node_selector.is_none());
+    }
+
+    #[test]
+    fn test_workspace_can_only_grow() {
+        assert!(check_workspace_growth("10Gi", "20Gi").is_ok());
+        assert!(check_workspace_growth("1Gi", "2G").is_ok());
+        assert!(check_workspace_growth("10Gi", "10Gi").is_err());
+        assert!(check_workspace_growth("10Gi", "5Gi").is_err());
+    }
+
+    #[test]
+    fn test_existing_workspace_pvc_is_mounted() {
+        let request = create_request(json!({ "name": "nb", "existing_workspace_pvc": "restored-data" }));
+        let spec = KubeflowManager.build_notebook_spec(&request).unwrap();
+
+        let volumes = spec.template.spec.volumes.unwrap();
+        let claim = volumes[0].persistent_volume_claim.as_ref().unwrap();
+        assert_eq!(claim.claim_name, "restored-data");
+    }
+
+    #[test]
+    fn test_explicit_service_account_is_used() {
+        let request = create_request(json!({ "name": "nb", "service_account": "notebook-restricted" }));
+        let spec = KubeflowManager.build_notebook_spec(&request).unwrap();
+        assert_eq!(spec.template.spec.service_account_name.as_deref(), Some("notebook-restricted"));
+
+        let request = create_request(json!({ "name": "nb" }));
+        let spec = KubeflowManager.build_notebook_spec(&request).unwrap();
+        assert_eq!(spec.template.spec.service_account_name, config::get().default_notebook_service_account);
+    }
+
+    #[test]
+    fn test_duplicate_mount_paths_are_rejected() {
+        let mount = |name: &str, path: &str| NotebookVolumeMount {
+            name: name.to_string(),
+            mount_path: path.to_string(),
+        };
+
+        assert!(check_mount_paths(&[mount("workspace", "/home/jovyan/work"), mount("scratch", "/tmp/scratch")]).is_ok());
+
+        let err = check_mount_paths(&[mount("workspace", "/home/jovyan/work"), mount("scratch", "/home/jovyan/work/")])
+            .unwrap_err();
+        match err {
+            AppError::Validation(msg) => {
+                assert!(msg.contains("/home/jovyan/work"));
+                assert!(msg.contains("'workspace' and 'scratch'"));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_resource_claims_are_kept_on_update() {
+        let request = create_request(json!({
+            "name": "nb",
+            "resource_claims": [{ "name": "gpu", "request": "a100" }]
+        }));
+        let existing = KubeflowManager.build_notebook_spec(&request).unwrap();
+        let resources = serde_json::to_value(&existing.template.spec.containers[0].resources).unwrap();
+        assert_eq!(resources, json!({ "claims": [{ "name": "gpu", "request": "a100" }] }));
+
+        let update: UpdateNotebookRequest = serde_json::from_value(json!({ "cpu_limit": "2" })).unwrap();
+        let updated = KubeflowManager.build_update_spec(&existing, &update).unwrap();
+        let claims = updated.template.spec.containers[0].resources.as_ref().unwrap().claims.as_ref();
+        assert_eq!(claims.map(Vec::len), Some(1));
+
+        let plain = KubeflowManager.build_notebook_spec(&create_request(json!({ "name": "nb" }))).unwrap();
+        assert!(plain.template.spec.containers[0].resources.is_none());
+    }
+
+    #[test]
+    fn test_command_and_args_are_set_and_cleared() {
+        let request = create_request(json!({
+            "name": "nb",
+            "command": ["jupyter", "lab"],
+            "args": ["--ServerApp.base_url=/notebook/team-a/nb"]
+        }));
+        let existing = KubeflowManager.build_notebook_spec(&request).unwrap();
+        let container = &existing.template.spec.containers[0];
+        assert_eq!(container.command, Some(vec!["jupyter".to_string(), "lab".to_string()]));
+        assert_eq!(container.args.as_ref().map(Vec::len), Some(1));
+
+        let update: UpdateNotebookRequest = serde_json::from_value(json!({ "command": [] })).unwrap();
+        let updated = KubeflowManager.build_update_spec(&existing, &update).unwrap();
+        assert_eq!(updated.template.spec.containers[0].command, None);
+        assert_eq!(updated.template.spec.containers[0].args, container.args);
+    }
+
+    #[test]
+    fn test_workspace_pvc_name() {
+        let request = create_request(json!({ "name": "nb", "existing_workspace_pvc": "restored-data" }));
+        let notebook = Notebook::new("nb", KubeflowManager.build_notebook_spec(&request).unwrap());
+        assert_eq!(workspace_pvc_name(&notebook, "nb").as_deref(), Some("restored-data"));
+
+        let request = create_request(json!({ "name": "nb" }));
+        let notebook = Notebook::new("nb", KubeflowManager.build_notebook_spec(&request).unwrap());
+        assert_eq!(workspace_pvc_name(&notebook, "nb"), None);
+    }
+
+    #[test]
+    fn test_debug_pod_mounts_workspace() {
+        let pod = debug_pod("nb", "team-a", "nb-workspace-pvc", "busybox:1.36", 600).unwrap();
+        let spec = pod.spec.unwrap();
+
+        assert_eq!(pod.metadata.name.as_deref(), Some("nb-debug"));
+        assert_eq!(pod.metadata.labels.unwrap().get("notebook").map(String::as_str), Some("nb"));
+        assert_eq!(spec.active_deadline_seconds, Some(600));
+        assert_eq!(spec.restart_policy.as_deref(), Some("Never"));
+        let claim = spec.volumes.unwrap()[0].persistent_volume_claim.clone().unwrap();
+        assert_eq!(claim.claim_name, "nb-workspace-pvc");
+    }
+
+    #[test]
+    fn test_workspace_pvc_labels_include_notebook() {
+        let request = create_request(json!({
+            "name": "nb",
+            "labels": { "team": "ml", "notebook": "spoofed" }
+        }));
+        let labels = KubeflowManager.workspace_pvc_labels(&request);
+
+        assert_eq!(labels.get("team").map(String::as_str), Some("ml"));
+        assert_eq!(labels.get("notebook").map(String::as_str), Some("nb"));
+    }
+
+    #[test]
+    fn test_update_image_pull_policy_keeps_other_fields() {
+        let request = create_request(json!({
+            "name": "nb",
+            "image": "jupyter/scipy-notebook:2024-01-15",
+            "cpu_limit": "2",
+            "environment_variables": { "MODE": "lab" }
+        }));
+        let existing = KubeflowManager.build_notebook_spec(&request).unwrap();
+
+        let update: UpdateNotebookRequest =
+            serde_json::from_value(json!({ "image_pull_policy": "Always" })).unwrap();
+        let updated = KubeflowManager.build_update_spec(&existing, &update).unwrap();
+
+        let before = &existing.template.spec.containers[0];
+        let after = &updated.template.spec.containers[0];
+        assert_eq!(after.image_pull_policy.as_deref(), Some("Always"));
+        assert_eq!(after.image, before.image);
+        assert_eq!(serde_json::to_value(&after.resources).unwrap(), serde_json::to_value(&before.resources).unwrap());
+        assert_eq!(serde_json::to_value(&after.env).unwrap(), serde_json::to_value(&before.env).unwrap());
+        assert_eq!(after.ports.as_ref().map(Vec::len), before.ports.as_ref().map(Vec::len));
+    }
+
+    #[test]
+    fn test_update_image_pull_policy_absent_and_null() {
+        let request = create_request(json!({ "name": "nb", "image_pull_policy": "IfNotPresent" }));
+        let existing = KubeflowManager.build_notebook_spec(&request).unwrap();
+
+        let absent: UpdateNotebookRequest = serde_json::from_value(json!({})).unwrap();
+        let updated = KubeflowManager.build_update_spec(&existing, &absent).unwrap();
+        assert_eq!(updated.template.spec.containers[0].image_pull_policy.as_deref(), Some("IfNotPresent"));
+
+        let cleared: UpdateNotebookRequest =
+            serde_json::from_value(json!({ "image_pull_policy": null })).unwrap();
+        let updated = KubeflowManager.build_update_spec(&existing, &cleared).unwrap();
+        assert_eq!(updated.template.spec.containers[0].image_pull_policy, None);
+    }
+
+    #[test]
+    fn test_cull_countdown() {
+        let now = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        let idle = Some(Duration::from_secs(3600));
+        let notebook = |annotations: Value| json!({ "metadata": { "name": "nb", "annotations": annotations } });
+
+        let mut active = notebook(json!({ LAST_ACTIVITY_ANNOTATION: "2024-05-01T11:45:00Z" }));
+        set_cull_countdown(&mut active, now, idle);
+        assert_eq!(active["seconds_until_cull"], 2700);
+
+        let mut overdue = notebook(json!({ LAST_ACTIVITY_ANNOTATION: "2024-05-01T10:00:00Z" }));
+        set_cull_countdown(&mut overdue, now, idle);
+        assert_eq!(overdue["seconds_until_cull"], 0);
+
+        let mut exempt = notebook(json!({
+            LAST_ACTIVITY_ANNOTATION: "2024-05-01T11:45:00Z",
+            NO_CULLING_ANNOTATION: "true"
+        }));
+        set_cull_countdown(&mut exempt, now, idle);
+        assert!(exempt.get("seconds_until_cull").is_none());
+
+        let mut unconfigured = notebook(json!({ LAST_ACTIVITY_ANNOTATION: "2024-05-01T11:45:00Z" }));
+        set_cull_countdown(&mut unconfigured, now, None);
+        assert!(unconfigured.get("seconds_until_cull").is_none());
+    }
+
+    #[test]
+    fn test_disable_culling_sets_annotation() {
+        let request = create_request(json!({ "name": "nb", "disable_culling": true }));
+        let annotations = KubeflowManager.notebook_annotations(&request).unwrap();
+        assert_eq!(annotations.get(NO_CULLING_ANNOTATION).map(String::as_str), Some("true"));
+
+        let request = create_request(json!({ "name": "nb", "disable_culling": false }));
+        assert!(KubeflowManager.notebook_annotations(&request).is_none());
+    }
+
+    #[test]
+    fn test_defaults_keep_explicit_image() {
+        let mut request = create_request(json!({ "name": "nb", "image": "jupyter/minimal-notebook:2024-01-15" }));
+        KubeflowManager.defaults(&mut request);
+        assert_eq!(request.image.as_deref(), Some("jupyter/minimal-notebook:2024-01-15"));
+    }
+
+    #[test]
+    fn test_defaults_fill_workspace_size_when_requested() {
+        let mut request = create_request(json!({ "name": "nb", "create_workspace": true }));
+        KubeflowManager.defaults(&mut request);
+        assert_eq!(request.workspace_volume_size, Some(config::get().default_workspace_size.clone()));
+
+        let mut request = create_request(json!({ "name": "nb", "create_workspace": true, "workspace_volume_size": "5Gi" }));
+        KubeflowManager.defaults(&mut request);
+        assert_eq!(request.workspace_volume_size.as_deref(), Some("5Gi"));
+
+        let mut request = create_request(json!({ "name": "nb" }));
+        KubeflowManager.defaults(&mut request);
+        assert_eq!(request.workspace_volume_size, None);
+    }
+
+    #[test]
+    fn test_gpu_notebook_gets_toleration() {
+        let gpu_key = config::get().gpu_toleration_key.clone();
+        let gpu_tolerations = |body: Value| {
+            let spec = KubeflowManager.build_notebook_spec(&create_request(body)).unwrap();
+            spec.template.spec.tolerations.unwrap_or_default()
+        };
+
+        let tolerations = gpu_tolerations(json!({ "name": "nb", "gpu_limit": "1" }));
+        assert_eq!(tolerations.len(), 1);
+        assert_eq!(tolerations[0].key.as_ref(), Some(&gpu_key));
+
+        // An explicit toleration for the taint is not duplicated
+        let tolerations = gpu_tolerations(json!({
+            "name": "nb",
+            "gpu_limit": "1",
+            "tolerations": [{ "key": gpu_key, "operator": "Equal", "value": "present", "effect": "NoSchedule" }]
+        }));
+        assert_eq!(tolerations.len(), 1);
+        assert_eq!(tolerations[0].value.as_deref(), Some("present"));
+
+        assert!(gpu_tolerations(json!({ "name": "nb", "gpu_limit": "1", "auto_gpu_toleration": false })).is_empty());
+        assert!(gpu_tolerations(json!({ "name": "nb" })).is_empty());
+    }
+
+    #[test]
+    fn test_pod_state() {
+        assert_eq!(pod_state(None), ("Pending".to_string(), false));
+
+        let pod: Pod = serde_json::from_value(json!({
+            "metadata": { "name": "nb-0" },
+            "status": {
+                "phase": "Running",
+                "conditions": [{ "type": "Ready", "status": "False" }]
+            }
+        }))
+        .unwrap();
+        assert_eq!(pod_state(Some(&pod)), ("Running".to_string(), false));
+
+        let pod: Pod = serde_json::from_value(json!({
+            "metadata": { "name": "nb-0" },
+            "status": {
+                "phase": "Running",
+                "conditions": [{ "type": "Ready", "status": "True" }]
+            }
+        }))
+        .unwrap();
+        assert_eq!(pod_state(Some(&pod)), ("Running".to_string(), true));
+
+        assert_eq!(notebook_url(None, "team-a", "nb"), "/notebook/team-a/nb/");
+        assert_eq!(notebook_url(Some("https://kubeflow.example.com"), "team-a", "nb"), "https://kubeflow.example.com/notebook/team-a/nb/");
+    }
+}
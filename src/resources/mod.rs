@@ -1,9 +1,132 @@
 pub mod cnpg;
+pub mod cnpg_database;
 pub mod kubeflow;
+pub mod quarantine;
 
-use crate::error::Result;
-use kube::Client;
+use crate::config;
+use crate::error::{AppError, Result};
+use crate::utils::{diff, field_validation, timeout, warnings};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+use k8s_openapi::chrono::{DateTime, Utc};
+use kube::{
+    api::{DeleteParams, ListParams, PatchParams, PostParams, PropagationPolicy},
+    Client, Resource,
+};
 use serde_json::Value;
+use tokio::sync::OnceCell;
+
+static CLIENT: OnceCell<Client> = OnceCell::const_new();
+
+/// The Kubernetes client shared by all requests, created from the environment (in-cluster or kubeconfig)
+/// on first use. `main` creates it at startup so a missing or broken configuration stops the server.
+///
+/// Clones share the connection pool. API warnings seen by the client are collected for the current request
+/// (see `utils::warnings`), writes carry the request's `fieldValidation` mode (see `utils::field_validation`),
+/// and responses are awaited for at most the request's Kubernetes API timeout (see `utils::timeout`).
+pub async fn kube_client() -> Result<Client> {
+    CLIENT.get_or_try_init(build_client).await.cloned()
+}
+
+async fn build_client() -> Result<Client> {
+    let mut config = kube::Config::infer()
+        .await
+        .map_err(|e| AppError::Config(format!("Failed to create Kubernetes client: {}", e)))?;
+    // Per-request timeouts are applied by `TimeoutLayer`; this only bounds reads on the socket
+    config.read_timeout = Some(config::get().max_kube_timeout);
+
+    let client = kube::client::ClientBuilder::try_from(config)
+        .map_err(|e| AppError::Config(format!("Failed to create Kubernetes client: {}", e)))?
+        .with_layer(&warnings::WarningLayer)
+        .with_layer(&field_validation::FieldValidationLayer)
+        .with_layer(&timeout::TimeoutLayer)
+        .build();
+
+    Ok(client)
+}
+
+/// Options for a single page of a list call.
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    pub limit: Option<u32>,
+    pub continue_token: Option<String>,
+    pub label_selector: Option<String>,
+    /// Only keep items created at or after this time (applied after fetching the page).
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only keep items created before this time (applied after fetching the page).
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+impl ListOptions {
+    pub fn list_params(&self) -> ListParams {
+        let mut params = ListParams::default();
+        if let Some(limit) = self.limit {
+            params = params.limit(limit);
+        }
+        if let Some(ref token) = self.continue_token {
+            params = params.continue_token(token);
+        }
+        if let Some(ref selector) = self.label_selector {
+            params = params.labels(selector);
+        }
+        params
+    }
+
+    /// Whether an item with the given `creationTimestamp` falls inside the requested creation-time window.
+    ///
+    /// Items without a timestamp only match when no window is set.
+    pub fn created_within(&self, created: Option<&Time>) -> bool {
+        if self.created_after.is_none() && self.created_before.is_none() {
+            return true;
+        }
+
+        let Some(Time(created)) = created else {
+            return false;
+        };
+
+        self.created_after.is_none_or(|after| *created >= after)
+            && self.created_before.is_none_or(|before| *created < before)
+    }
+}
+
+/// Field manager recorded for server-side apply writes made by this service.
+pub const FIELD_MANAGER: &str = "k8s-resource-manager";
+
+/// Options for a create/update call.
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    /// Ask the API server to validate and default the write without persisting it.
+    pub dry_run: bool,
+    /// On create, treat an existing resource with an equivalent spec as success instead of a conflict.
+    pub if_not_exists: bool,
+    /// On server-side apply updates, take over fields owned by another field manager instead of failing with a conflict.
+    pub force_ownership: bool,
+}
+
+impl WriteOptions {
+    pub fn post_params(&self) -> PostParams {
+        PostParams {
+            dry_run: self.dry_run,
+            ..Default::default()
+        }
+    }
+
+    pub fn patch_params(&self) -> PatchParams {
+        PatchParams {
+            dry_run: self.dry_run,
+            ..Default::default()
+        }
+    }
+
+    /// Server-side apply as `FIELD_MANAGER`, forced when `force_ownership` is set.
+    pub fn apply_params(&self) -> PatchParams {
+        PatchParams {
+            dry_run: self.dry_run,
+            force: self.force_ownership,
+            field_manager: Some(FIELD_MANAGER.to_string()),
+            ..Default::default()
+        }
+    }
+}
 
 #[async_trait::async_trait]
 pub trait ResourceManager {
@@ -11,15 +134,314 @@ pub trait ResourceManager {
     type UpdateRequest;
     type Resource;
 
-    async fn create(&self, client: Client, request: Self::CreateRequest) -> Result<Value>;
+    /// Fills in resource-specific defaults for fields the caller omitted.
+    ///
+    /// Called by `create` before the spec is built.
+    fn defaults(&self, _request: &mut Self::CreateRequest) {}
+
+    /// Creates the resource.
+    ///
+    /// With `options.if_not_exists`, an existing resource whose spec matches the request is
+    /// returned with `"created": false`; one that differs fails with a conflict listing the differences.
+    async fn create(&self, client: Client, request: Self::CreateRequest, options: &WriteOptions) -> Result<Value>;
     async fn get(&self, client: Client, namespace: &str, name: &str) -> Result<Self::Resource>;
-    async fn list(&self, client: Client, namespace: &str) -> Result<Value>;
+    async fn list(&self, client: Client, namespace: &str, options: &ListOptions) -> Result<Value>;
+
+    /// Applies `request` to an existing resource.
+    ///
+    /// With `options.dry_run` nothing is persisted and the object the server would have stored is returned.
     async fn update(
         &self,
         client: Client,
         namespace: &str,
         name: &str,
         request: Self::UpdateRequest,
+        options: &WriteOptions,
     ) -> Result<Value>;
-    async fn delete(&self, client: Client, namespace: &str, name: &str) -> Result<Value>;
-}
\ No newline at end of file
+    async fn delete(&self, client: Client, namespace: &str, name: &str, params: &DeleteParams) -> Result<Value>;
+}
+
+/// Parses a Kubernetes delete propagation policy (`Orphan`, `Background` or `Foreground`).
+pub fn parse_propagation_policy(value: &str) -> Result<PropagationPolicy> {
+    match value.to_ascii_lowercase().as_str() {
+        "orphan" => Ok(PropagationPolicy::Orphan),
+        "background" => Ok(PropagationPolicy::Background),
+        "foreground" => Ok(PropagationPolicy::Foreground),
+        _ => Err(AppError::BadRequest(format!(
+            "Invalid propagation policy '{}': expected Orphan, Background or Foreground",
+            value
+        ))),
+    }
+}
+
+/// Delete parameters for a request: its own propagation policy, else the configured default.
+pub fn delete_params(propagation_policy: Option<&str>) -> Result<DeleteParams> {
+    let propagation_policy = match propagation_policy {
+        Some(value) => Some(parse_propagation_policy(value)?),
+        None => config::get().default_propagation_policy.clone(),
+    };
+
+    Ok(DeleteParams {
+        propagation_policy,
+        ..Default::default()
+    })
+}
+
+/// Sets `apiVersion` and `kind` of a list result and of each of its items (`items` or `resources`).
+///
+/// The API server omits them on list items, and clients need them to `kubectl apply` an item back.
+pub fn set_list_type_meta<K: Resource<DynamicType = ()>>(list: &mut Value) {
+    let (api_version, kind) = (K::api_version(&()), K::kind(&()));
+
+    if let Some(items) = list.get_mut("items").and_then(Value::as_array_mut) {
+        for item in items.iter_mut() {
+            set_type_meta(item, &api_version, &kind);
+        }
+        set_type_meta(list, &api_version, &format!("{}List", kind));
+    } else if let Some(items) = list.get_mut("resources").and_then(Value::as_array_mut) {
+        for item in items.iter_mut() {
+            set_type_meta(item, &api_version, &kind);
+        }
+    }
+}
+
+/// Adds `age_seconds` and a short `age` (e.g. `3d`) to each item of a list result, relative to `now`.
+///
+/// Reads `metadata.creationTimestamp`, or `creation_timestamp` on summaries. Items without one get `null`.
+pub fn set_list_age(list: &mut Value, now: DateTime<Utc>) {
+    let key = if list.get("items").is_some() { "items" } else { "resources" };
+    let items = list.get_mut(key).and_then(Value::as_array_mut);
+
+    for item in items.into_iter().flatten() {
+        let age_seconds = item
+            .pointer("/metadata/creationTimestamp")
+            .or_else(|| item.get("creation_timestamp"))
+            .and_then(Value::as_str)
+            .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+            .map(|created| (now - created.with_timezone(&Utc)).num_seconds().max(0));
+
+        if let Some(object) = item.as_object_mut() {
+            object.insert("age_seconds".to_string(), age_seconds.into());
+            object.insert("age".to_string(), age_seconds.map(format_age).into());
+        }
+    }
+}
+
+/// Formats an age in its largest whole unit, as `kubectl get` does: `45s`, `12m`, `5h`, `3d`, `2y`.
+fn format_age(seconds: i64) -> String {
+    match seconds {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86400 => format!("{}h", s / 3600),
+        s if s < 365 * 86400 => format!("{}d", s / 86400),
+        s => format!("{}y", s / (365 * 86400)),
+    }
+}
+
+fn set_type_meta(object: &mut Value, api_version: &str, kind: &str) {
+    if let Some(object) = object.as_object_mut() {
+        object.insert("apiVersion".to_string(), api_version.into());
+        object.insert("kind".to_string(), kind.into());
+    }
+}
+
+/// Whether a create failed because the object already exists.
+pub fn is_already_exists(error: &kube::Error) -> bool {
+    matches!(error, kube::Error::Api(response) if response.code == 409 && response.reason == "AlreadyExists")
+}
+
+/// Checks that an existing object's spec matches the spec a create would have written.
+///
+/// Fields only present on the existing object (server defaults) are not a difference.
+pub fn ensure_spec_matches(kind: &str, name: &str, existing: &Value, desired: &Value) -> Result<()> {
+    let differences: Vec<Value> = diff::object_changes(existing, desired)
+        .into_iter()
+        .filter(|change| change["op"] != "remove")
+        .collect();
+
+    if differences.is_empty() {
+        return Ok(());
+    }
+
+    Err(AppError::Conflict(
+        format!("{} '{}' already exists with a different spec", kind, name),
+        Value::Array(differences),
+    ))
+}
+
+/// Turns the API server's refusal of a dry-run request into a clear client error.
+///
+/// Servers reject dry runs when e.g. an admission webhook on the resource declares side effects.
+pub fn dry_run_error(error: AppError) -> AppError {
+    match error {
+        AppError::Kube(kube::Error::Api(ref response))
+            if response.code == 400 && response.message.to_ascii_lowercase().contains("dry run") =>
+        {
+            AppError::BadRequest(format!(
+                "Server-side dry run is not supported for this resource: {}",
+                response.message
+            ))
+        }
+        other => other,
+    }
+}
+
+/// Whether an error means the Kubernetes API server could not be reached or did not answer in time,
+/// as opposed to an answer rejecting the request.
+pub fn is_unreachable(error: &AppError) -> bool {
+    match error {
+        AppError::Kube(kube::Error::Api(_)) => false,
+        AppError::Kube(_) | AppError::Timeout(_) | AppError::Config(_) => true,
+        _ => false,
+    }
+}
+
+/// A client whose API server answers every request with a `Status` failure of this code and reason.
+#[cfg(test)]
+pub fn failing_client(status: u16, reason: &'static str) -> Client {
+    let service = tower::service_fn(move |_: axum::http::Request<kube::client::Body>| async move {
+        let body = serde_json::json!({
+            "kind": "Status",
+            "apiVersion": "v1",
+            "status": "Failure",
+            "message": "denied",
+            "reason": reason,
+            "code": status
+        });
+        axum::http::Response::builder()
+            .status(status)
+            .body(kube::client::Body::from(body.to_string().into_bytes()))
+    });
+    Client::new(service, "default")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_is_unreachable() {
+        let rejected = kube::error::ErrorResponse {
+            status: "Failure".to_string(),
+            message: "admission webhook denied the request".to_string(),
+            reason: "Forbidden".to_string(),
+            code: 403,
+        };
+        assert!(!is_unreachable(&AppError::Kube(kube::Error::Api(rejected))));
+        assert!(!is_unreachable(&AppError::Validation("instances".to_string())));
+        assert!(is_unreachable(&AppError::Timeout("no answer".to_string())));
+        assert!(is_unreachable(&AppError::Kube(kube::Error::Service(Box::new(timeout::TimedOut(
+            std::time::Duration::from_secs(30)
+        ))))));
+    }
+
+    #[test]
+    fn test_apply_params_force_ownership() {
+        let patch = kube::api::Patch::Apply(json!({ "spec": {} }));
+        let request = kube::core::Request::new("/apis/kubeflow.org/v1/namespaces/default/notebooks");
+
+        let options = WriteOptions::default();
+        let url = request.patch("nb", &options.apply_params(), &patch).unwrap().uri().to_string();
+        assert!(url.contains("fieldManager=k8s-resource-manager"));
+        assert!(!url.contains("force"));
+
+        let options = WriteOptions {
+            force_ownership: true,
+            ..Default::default()
+        };
+        let url = request.patch("nb", &options.apply_params(), &patch).unwrap().uri().to_string();
+        assert!(url.contains("force=true"));
+    }
+
+    #[test]
+    fn test_set_list_type_meta() {
+        use crate::models::{cnpg::Cluster, kubeflow::Notebook};
+
+        let mut list = json!({ "metadata": {}, "items": [{ "metadata": { "name": "nb" } }] });
+        set_list_type_meta::<Notebook>(&mut list);
+        assert_eq!(list["apiVersion"], "kubeflow.org/v1");
+        assert_eq!(list["kind"], "NotebookList");
+        assert_eq!(list["items"][0]["apiVersion"], "kubeflow.org/v1");
+        assert_eq!(list["items"][0]["kind"], "Notebook");
+
+        let mut summary = json!({ "resources": [{ "name": "db" }], "count": 1 });
+        set_list_type_meta::<Cluster>(&mut summary);
+        assert_eq!(summary["resources"][0]["apiVersion"], "postgresql.cnpg.io/v1");
+        assert_eq!(summary["resources"][0]["kind"], "Cluster");
+        assert!(summary.get("kind").is_none());
+    }
+
+    #[test]
+    fn test_set_list_age() {
+        let now = DateTime::parse_from_rfc3339("2024-01-04T00:00:00Z").unwrap().with_timezone(&Utc);
+
+        let mut list = json!({ "items": [
+            { "metadata": { "creationTimestamp": "2024-01-01T00:00:00Z" } },
+            { "metadata": {} }
+        ] });
+        set_list_age(&mut list, now);
+        assert_eq!(list["items"][0]["age_seconds"], 3 * 86400);
+        assert_eq!(list["items"][0]["age"], "3d");
+        assert!(list["items"][1]["age_seconds"].is_null());
+        assert!(list["items"][1]["age"].is_null());
+
+        // Clock skew can put the timestamp slightly in the future
+        let mut summary = json!({ "resources": [{ "creation_timestamp": "2024-01-04T00:00:05Z" }] });
+        set_list_age(&mut summary, now);
+        assert_eq!(summary["resources"][0]["age"], "0s");
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(59), "59s");
+        assert_eq!(format_age(60), "1m");
+        assert_eq!(format_age(7200), "2h");
+        assert_eq!(format_age(2 * 365 * 86400), "2y");
+    }
+
+    #[test]
+    fn test_created_within_window() {
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let options = ListOptions {
+            created_after: Some(at("2024-01-01T00:00:00Z")),
+            created_before: Some(at("2024-02-01T00:00:00Z")),
+            ..Default::default()
+        };
+
+        assert!(options.created_within(Some(&Time(at("2024-01-01T00:00:00Z")))));
+        assert!(options.created_within(Some(&Time(at("2024-01-15T12:00:00+02:00")))));
+        assert!(!options.created_within(Some(&Time(at("2024-02-01T00:00:00Z")))));
+        assert!(!options.created_within(Some(&Time(at("2023-12-31T23:59:59Z")))));
+        assert!(!options.created_within(None));
+        assert!(ListOptions::default().created_within(None));
+    }
+
+    #[test]
+    fn test_delete_params_prefers_request_policy() {
+        let params = delete_params(Some("foreground")).unwrap();
+        assert!(matches!(params.propagation_policy, Some(PropagationPolicy::Foreground)));
+
+        let params = delete_params(None).unwrap();
+        assert_eq!(
+            format!("{:?}", params.propagation_policy),
+            format!("{:?}", config::get().default_propagation_policy)
+        );
+
+        assert!(matches!(delete_params(Some("cascade")), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_ensure_spec_matches_ignores_server_defaults() {
+        let existing = json!({ "spec": { "instances": 1, "enableSuperuserAccess": false } });
+
+        assert!(ensure_spec_matches("CNPG cluster", "db", &existing, &json!({ "spec": { "instances": 1 } })).is_ok());
+
+        match ensure_spec_matches("CNPG cluster", "db", &existing, &json!({ "spec": { "instances": 3 } })) {
+            Err(AppError::Conflict(_, details)) => {
+                assert_eq!(details, json!([{ "op": "replace", "path": "/spec/instances", "from": 1, "to": 3 }]));
+            }
+            other => panic!("expected conflict, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! Soft delete: resources are labelled, scaled down and only deleted after a grace period.
+
+use crate::config;
+use crate::error::{AppError, Result};
+use crate::registry::{Registry, ResourceModule};
+use crate::resources;
+use crate::utils::supervisor::{self, TaskOptions};
+use k8s_openapi::chrono::{DateTime, Duration as ChronoDuration, Utc};
+use kube::api::{Api, DynamicObject, ListParams, Patch, PatchParams};
+use kube::{Client, ResourceExt};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+pub const LIFECYCLE_LABEL: &str = "lifecycle";
+pub const QUARANTINED: &str = "quarantined";
+/// RFC 3339 time the resource was quarantined; the grace period counts from here.
+pub const QUARANTINED_AT_ANNOTATION: &str = "k8s-resource-manager/quarantined-at";
+
+/// How often the reaper looks for quarantined resources past their grace period.
+const REAP_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Labels the resource as quarantined and scales it down instead of deleting it.
+pub async fn quarantine(client: Client, module: &dyn ResourceModule, namespace: &str, name: &str) -> Result<Value> {
+    let api = dynamic_api(client, module, namespace);
+    let object = get(&api, module, namespace, name).await?;
+
+    if is_quarantined(&object) {
+        return Err(AppError::Conflict(
+            format!("{} '{}' is already quarantined", module.kind(), name),
+            summary(module, &object),
+        ));
+    }
+
+    let (suspend_key, suspend_value) = module.suspend_annotation();
+    let patch = json!({
+        "metadata": {
+            "labels": { LIFECYCLE_LABEL: QUARANTINED },
+            "annotations": {
+                QUARANTINED_AT_ANNOTATION: Utc::now().to_rfc3339(),
+                suspend_key: suspend_value
+            }
+        }
+    });
+    let object = api.patch(name, &PatchParams::default(), &Patch::Merge(&patch)).await?;
+
+    tracing::info!(resource = module.name(), name = name, namespace = namespace, "Resource quarantined");
+
+    let mut result = summary(module, &object);
+    result["message"] = format!("{} '{}' quarantined", module.kind(), name).into();
+    Ok(result)
+}
+
+/// Removes the quarantine label and scales the resource back up.
+pub async fn restore(client: Client, module: &dyn ResourceModule, namespace: &str, name: &str) -> Result<Value> {
+    let api = dynamic_api(client, module, namespace);
+    let object = get(&api, module, namespace, name).await?;
+    ensure_quarantined(module, &object)?;
+
+    let (suspend_key, _) = module.suspend_annotation();
+    let patch = json!({
+        "metadata": {
+            "labels": { LIFECYCLE_LABEL: null },
+            "annotations": { QUARANTINED_AT_ANNOTATION: null, suspend_key: null }
+        }
+    });
+    api.patch(name, &PatchParams::default(), &Patch::Merge(&patch)).await?;
+
+    tracing::info!(resource = module.name(), name = name, namespace = namespace, "Resource restored from quarantine");
+
+    Ok(json!({
+        "message": format!("{} '{}' restored", module.kind(), name),
+        "resource": module.name(),
+        "name": name,
+        "namespace": namespace
+    }))
+}
+
+/// Deletes a quarantined resource without waiting for the grace period.
+pub async fn purge(client: Client, module: &dyn ResourceModule, namespace: &str, name: &str) -> Result<Value> {
+    let api = dynamic_api(client, module, namespace);
+    let object = get(&api, module, namespace, name).await?;
+    ensure_quarantined(module, &object)?;
+
+    api.delete(name, &resources::delete_params(None)?).await?;
+
+    tracing::info!(resource = module.name(), name = name, namespace = namespace, "Quarantined resource purged");
+
+    Ok(json!({
+        "message": format!("{} '{}' deleted", module.kind(), name),
+        "resource": module.name(),
+        "name": name,
+        "namespace": namespace
+    }))
+}
+
+/// Quarantined resources of every enabled type in `namespace`.
+pub async fn list(client: Client, registry: &Registry, namespace: &str) -> Result<Value> {
+    let params = ListParams::default().labels(&format!("{}={}", LIFECYCLE_LABEL, QUARANTINED));
+    let mut items = Vec::new();
+
+    for module in registry.modules() {
+        let api = dynamic_api(client.clone(), module.as_ref(), namespace);
+        let objects = api.list(&params).await?;
+        items.extend(objects.items.iter().map(|object| summary(module.as_ref(), object)));
+    }
+
+    Ok(json!({
+        "items": items,
+        "count": items.len(),
+        "namespace": namespace
+    }))
+}
+
+/// Deletes quarantined resources whose grace period has passed, in the allowed namespaces or cluster-wide.
+pub async fn purge_expired(client: Client, registry: &Registry, now: DateTime<Utc>) -> Result<usize> {
+    let params = ListParams::default().labels(&format!("{}={}", LIFECYCLE_LABEL, QUARANTINED));
+    let mut purged = 0;
+
+    for module in registry.modules() {
+        let api_resource = module.api_resource();
+        let scopes: Vec<Api<DynamicObject>> = match config::get().allowed_namespaces.as_deref() {
+            Some(namespaces) => namespaces
+                .iter()
+                .map(|namespace| Api::namespaced_with(client.clone(), namespace, &api_resource))
+                .collect(),
+            None => vec![Api::all_with(client.clone(), &api_resource)],
+        };
+
+        for api in scopes {
+            for object in api.list(&params).await?.items {
+                if purge_after(&object).is_none_or(|deadline| deadline > now) {
+                    continue;
+                }
+
+                let namespace = object.namespace().unwrap_or_default();
+                let namespaced = dynamic_api(client.clone(), module.as_ref(), &namespace);
+                match namespaced.delete(&object.name_any(), &resources::delete_params(None)?).await {
+                    Ok(_) => {
+                        tracing::info!(resource = module.name(), name = %object.name_any(), namespace = %namespace, "Quarantine grace period expired; resource deleted");
+                        purged += 1;
+                    }
+                    Err(e) => tracing::warn!(resource = module.name(), name = %object.name_any(), namespace = %namespace, error = %e, "Failed to delete quarantined resource"),
+                }
+            }
+        }
+    }
+
+    Ok(purged)
+}
+
+/// Periodically deletes quarantined resources past `QUARANTINE_GRACE_PERIOD_SECONDS`.
+///
+/// Critical: while the reaper is down quarantined resources are never cleaned up, so the service reports not ready.
+pub fn spawn_reaper(registry: &'static Registry) {
+    let options = TaskOptions {
+        critical: true,
+        restart: true,
+    };
+    supervisor::spawn("quarantine-reaper", options, move || reap(registry));
+}
+
+async fn reap(registry: &'static Registry) {
+    let mut ticker = tokio::time::interval(REAP_INTERVAL);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        let result = match resources::kube_client().await {
+            Ok(client) => purge_expired(client, registry, Utc::now()).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "Quarantine reaper run failed");
+        }
+    }
+}
+
+fn dynamic_api(client: Client, module: &dyn ResourceModule, namespace: &str) -> Api<DynamicObject> {
+    Api::namespaced_with(client, namespace, &module.api_resource())
+}
+
+async fn get(api: &Api<DynamicObject>, module: &dyn ResourceModule, namespace: &str, name: &str) -> Result<DynamicObject> {
+    match api.get(name).await {
+        Err(kube::Error::Api(err)) if err.code == 404 => Err(AppError::NotFound(format!(
+            "{} '{}' not found in namespace '{}'",
+            module.kind(),
+            name,
+            namespace
+        ))),
+        result => Ok(result?),
+    }
+}
+
+fn is_quarantined(object: &DynamicObject) -> bool {
+    object.labels().get(LIFECYCLE_LABEL).map(String::as_str) == Some(QUARANTINED)
+}
+
+fn ensure_quarantined(module: &dyn ResourceModule, object: &DynamicObject) -> Result<()> {
+    if is_quarantined(object) {
+        Ok(())
+    } else {
+        Err(AppError::Conflict(
+            format!("{} '{}' is not quarantined", module.kind(), object.name_any()),
+            json!({ "resource": module.name(), "name": object.name_any() }),
+        ))
+    }
+}
+
+fn quarantined_at(object: &DynamicObject) -> Option<DateTime<Utc>> {
+    let value = object.annotations().get(QUARANTINED_AT_ANNOTATION)?;
+    DateTime::parse_from_rfc3339(value).ok().map(|time| time.with_timezone(&Utc))
+}
+
+/// When the resource becomes eligible for deletion. Without a readable timestamp it is never purged automatically.
+fn purge_after(object: &DynamicObject) -> Option<DateTime<Utc>> {
+    let grace = ChronoDuration::from_std(config::get().quarantine_grace_period).ok()?;
+    quarantined_at(object)?.checked_add_signed(grace)
+}
+
+fn summary(module: &dyn ResourceModule, object: &DynamicObject) -> Value {
+    json!({
+        "resource": module.name(),
+        "kind": module.kind(),
+        "name": object.name_any(),
+        "namespace": object.namespace(),
+        "quarantined_at": quarantined_at(object).map(|time| time.to_rfc3339()),
+        "purge_after": purge_after(object).map(|time| time.to_rfc3339())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(labels: Value, annotations: Value) -> DynamicObject {
+        serde_json::from_value(json!({
+            "apiVersion": "postgresql.cnpg.io/v1",
+            "kind": "Cluster",
+            "metadata": { "name": "db", "namespace": "default", "labels": labels, "annotations": annotations }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_purge_after_counts_from_quarantine_time() {
+        let quarantined = object(
+            json!({ "lifecycle": "quarantined" }),
+            json!({ QUARANTINED_AT_ANNOTATION: "2024-01-01T00:00:00Z" }),
+        );
+        assert!(is_quarantined(&quarantined));
+
+        let expected = DateTime::parse_from_rfc3339("2024-01-08T00:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(purge_after(&quarantined), Some(expected));
+    }
+
+    #[test]
+    fn test_unreadable_timestamp_is_never_purged() {
+        let quarantined = object(json!({ "lifecycle": "quarantined" }), json!({ QUARANTINED_AT_ANNOTATION: "yesterday" }));
+        assert_eq!(purge_after(&quarantined), None);
+        assert!(!is_quarantined(&object(json!({ "lifecycle": "active" }), json!({}))));
+    }
+}
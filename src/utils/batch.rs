@@ -0,0 +1,109 @@
+use crate::error::Result;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json as ResponseJson, Response},
+};
+use serde_json::{json, Value};
+
+/// Per-item results of a batch operation, in request order.
+///
+/// The response status summarizes them: 200 when every item succeeded, 400 when every item failed and
+/// 207 Multi-Status otherwise. Each item carries its own status.
+#[derive(Debug, Default)]
+pub struct BatchResponse {
+    items: Vec<Result<Value>>,
+}
+
+impl BatchResponse {
+    pub fn push(&mut self, result: Result<Value>) {
+        self.items.push(result);
+    }
+
+    fn status(&self) -> StatusCode {
+        let failed = self.items.iter().filter(|item| item.is_err()).count();
+        if failed == 0 {
+            StatusCode::OK
+        } else if failed == self.items.len() {
+            StatusCode::BAD_REQUEST
+        } else {
+            StatusCode::MULTI_STATUS
+        }
+    }
+}
+
+impl IntoResponse for BatchResponse {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let total = self.items.len();
+
+        let items: Vec<Value> = self
+            .items
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| match result {
+                Ok(result) => json!({ "index": index, "status": StatusCode::OK.as_u16(), "result": result }),
+                Err(error) => {
+                    let (status, mut body) = error.into_body();
+                    json!({ "index": index, "status": status.as_u16(), "error": body["error"].take() })
+                }
+            })
+            .collect();
+        let failed = items.iter().filter(|item| item.get("error").is_some()).count();
+
+        let body = json!({
+            "items": items,
+            "succeeded": total - failed,
+            "failed": failed
+        });
+        (status, ResponseJson(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+
+    fn batch(results: Vec<Result<Value>>) -> BatchResponse {
+        let mut batch = BatchResponse::default();
+        for result in results {
+            batch.push(result);
+        }
+        batch
+    }
+
+    fn not_found() -> Result<Value> {
+        Err(AppError::NotFound("CNPG cluster 'db' not found".to_string()))
+    }
+
+    #[test]
+    fn test_all_items_succeed() {
+        let response = batch(vec![Ok(json!({ "name": "a" })), Ok(json!({ "name": "b" }))]).into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_some_items_fail() {
+        let response = batch(vec![Ok(json!({ "name": "a" })), not_found()]).into_response();
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+    }
+
+    #[test]
+    fn test_all_items_fail() {
+        let response = batch(vec![not_found(), not_found()]).into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_items_carry_their_own_status() {
+        let response = batch(vec![Ok(json!({ "name": "a" })), not_found()]).into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(body["succeeded"], 1);
+        assert_eq!(body["failed"], 1);
+        assert_eq!(body["items"][0]["status"], 200);
+        assert_eq!(body["items"][1]["status"], 404);
+        assert_eq!(body["items"][1]["error"]["type"], "NotFound");
+    }
+}
@@ -0,0 +1,129 @@
+//! Single-flight reads: identical get/list calls in flight at the same time share one Kubernetes API request.
+
+use crate::error::{AppError, Result};
+use crate::resources::ListOptions;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, LazyLock, Mutex};
+
+type SharedCall = Shared<BoxFuture<'static, std::result::Result<Value, Arc<AppError>>>>;
+
+static IN_FLIGHT: LazyLock<Mutex<HashMap<String, SharedCall>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Key for reading a single resource.
+pub fn get_key(resource: &str, namespace: &str, name: &str) -> String {
+    format!("get/{}/{}/{}", resource, namespace, name)
+}
+
+/// Key for a list page; every option that changes the result is part of it.
+pub fn list_key(resource: &str, namespace: &str, options: &ListOptions) -> String {
+    format!(
+        "list/{}/{}?limit={:?}&continue={:?}&selector={:?}&after={:?}&before={:?}",
+        resource,
+        namespace,
+        options.limit,
+        options.continue_token,
+        options.label_selector,
+        options.created_after,
+        options.created_before
+    )
+}
+
+/// Runs `call`, unless an identical call (same `key`) is already in flight, in which case its result is shared.
+///
+/// Every waiter gets the outcome of the one call, errors included.
+pub async fn run<F>(key: String, call: F) -> Result<Value>
+where
+    F: Future<Output = Result<Value>> + Send + 'static,
+{
+    let shared = {
+        let mut in_flight = IN_FLIGHT.lock().unwrap();
+        match in_flight.get(&key) {
+            Some(shared) => {
+                tracing::debug!(key = %key, "Joining in-flight request");
+                shared.clone()
+            }
+            None => {
+                let cleanup_key = key.clone();
+                let shared = async move {
+                    let result = call.await.map_err(Arc::new);
+                    IN_FLIGHT.lock().unwrap().remove(&cleanup_key);
+                    result
+                }
+                .boxed()
+                .shared();
+                in_flight.insert(key, shared.clone());
+                shared
+            }
+        }
+    };
+
+    shared.await.map_err(|error| Arc::try_unwrap(error).unwrap_or_else(|error| duplicate(&error)))
+}
+
+/// A copy of `error` for another waiter, keeping its type (and so its status code).
+fn duplicate(error: &AppError) -> AppError {
+    match error {
+        AppError::Kube(kube::Error::Api(response)) => AppError::Kube(kube::Error::Api(response.clone())),
+        AppError::NotFound(msg) => AppError::NotFound(msg.clone()),
+        AppError::BadRequest(msg) => AppError::BadRequest(msg.clone()),
+        AppError::Validation(msg) => AppError::Validation(msg.clone()),
+        AppError::Config(msg) => AppError::Config(msg.clone()),
+        AppError::Timeout(msg) => AppError::Timeout(msg.clone()),
+        AppError::TooManyRequests(msg) => AppError::TooManyRequests(msg.clone()),
+        AppError::Unauthorized(msg) => AppError::Unauthorized(msg.clone()),
+        AppError::Forbidden(msg) => AppError::Forbidden(msg.clone()),
+        AppError::Unavailable(msg) => AppError::Unavailable(msg.clone()),
+        AppError::Conflict(msg, details) => AppError::Conflict(msg.clone(), details.clone()),
+        AppError::Internal(msg) => AppError::Internal(msg.clone()),
+        other => AppError::Internal(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_calls_share_one_request() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let call = |calls: Arc<AtomicUsize>| async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(json!({ "name": "db" }))
+        };
+
+        let key = get_key("cnpg", "coalesce-test", "db");
+        let (first, second) = tokio::join!(
+            run(key.clone(), call(calls.clone())),
+            run(key.clone(), call(calls.clone()))
+        );
+
+        assert_eq!(first.unwrap(), json!({ "name": "db" }));
+        assert_eq!(second.unwrap(), json!({ "name": "db" }));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Once finished, the next call goes to the API server again
+        run(key, call(calls.clone())).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_errors_reach_every_waiter() {
+        let call = || async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Err(AppError::NotFound("CNPG cluster 'db' not found".to_string()))
+        };
+
+        let key = get_key("cnpg", "coalesce-test", "missing");
+        let (first, second) = tokio::join!(run(key.clone(), call()), run(key, call()));
+
+        assert!(matches!(first, Err(AppError::NotFound(_))));
+        assert!(matches!(second, Err(AppError::NotFound(_))));
+    }
+}
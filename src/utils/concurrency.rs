@@ -0,0 +1,149 @@
+use crate::config;
+use crate::error::{AppError, Result};
+use crate::metrics::NAMESPACE_QUEUE_DEPTH;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+static GATE: LazyLock<NamespaceGate> = LazyLock::new(|| {
+    let config = config::get();
+    NamespaceGate::new(
+        config.namespace_max_concurrent_mutations,
+        config.namespace_max_queued_mutations,
+    )
+});
+
+/// Waits for a mutation slot in `namespace`, or fails with 429 once the namespace's queue is full.
+///
+/// Hold the returned permit for the duration of the Kubernetes write.
+pub async fn acquire_mutation_slot(namespace: &str) -> Result<OwnedSemaphorePermit> {
+    GATE.acquire(namespace).await
+}
+
+/// Limits concurrent mutating operations per namespace, with a bounded wait queue.
+struct NamespaceGate {
+    limit: usize,
+    max_queued: usize,
+    namespaces: Mutex<HashMap<String, Arc<NamespaceSlot>>>,
+}
+
+struct NamespaceSlot {
+    semaphore: Arc<Semaphore>,
+    queued: AtomicUsize,
+}
+
+/// Keeps the queue depth accurate even if the waiting request is cancelled.
+struct QueuedGuard<'a> {
+    namespace: &'a str,
+    slot: &'a NamespaceSlot,
+}
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) {
+        let queued = self.slot.queued.fetch_sub(1, Ordering::SeqCst) - 1;
+        NAMESPACE_QUEUE_DEPTH
+            .with_label_values(&[self.namespace])
+            .set(queued as i64);
+    }
+}
+
+impl NamespaceGate {
+    fn new(limit: usize, max_queued: usize) -> Self {
+        Self {
+            limit,
+            max_queued,
+            namespaces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn slot(&self, namespace: &str) -> Arc<NamespaceSlot> {
+        let mut namespaces = self
+            .namespaces
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        namespaces
+            .entry(namespace.to_string())
+            .or_insert_with(|| {
+                Arc::new(NamespaceSlot {
+                    semaphore: Arc::new(Semaphore::new(self.limit)),
+                    queued: AtomicUsize::new(0),
+                })
+            })
+            .clone()
+    }
+
+    async fn acquire(&self, namespace: &str) -> Result<OwnedSemaphorePermit> {
+        let slot = self.slot(namespace);
+
+        if let Ok(permit) = slot.semaphore.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+
+        let queued = slot.queued.fetch_add(1, Ordering::SeqCst) + 1;
+        let guard = QueuedGuard {
+            namespace,
+            slot: &slot,
+        };
+
+        if queued > self.max_queued {
+            drop(guard);
+            return Err(AppError::TooManyRequests(format!(
+                "Too many concurrent changes in namespace '{}', retry later",
+                namespace
+            )));
+        }
+
+        NAMESPACE_QUEUE_DEPTH
+            .with_label_values(&[namespace])
+            .set(queued as i64);
+
+        let permit = slot
+            .semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| AppError::Internal("Namespace concurrency gate closed".to_string()));
+        drop(guard);
+
+        permit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_gate_rejects_when_queue_full() {
+        let gate = NamespaceGate::new(1, 0);
+
+        let held = gate.acquire("team-a").await.unwrap();
+        assert!(matches!(
+            gate.acquire("team-a").await,
+            Err(AppError::TooManyRequests(_))
+        ));
+
+        // Other namespaces are unaffected
+        assert!(gate.acquire("team-b").await.is_ok());
+
+        drop(held);
+        assert!(gate.acquire("team-a").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_gate_queues_within_bound() {
+        let gate = Arc::new(NamespaceGate::new(1, 1));
+        let held = gate.acquire("team-a").await.unwrap();
+
+        let waiter = {
+            let gate = gate.clone();
+            tokio::spawn(async move { gate.acquire("team-a").await.map(|_| ()) })
+        };
+        tokio::task::yield_now().await;
+        drop(held);
+
+        assert!(waiter.await.unwrap().is_ok());
+    }
+}
@@ -0,0 +1,122 @@
+use serde_json::{json, Map, Value};
+
+/// Metadata the API server rewrites on every write; left out of object diffs as noise.
+const IGNORED_METADATA: &[&str] = &["managedFields", "resourceVersion", "generation"];
+
+/// Lists the changes between two versions of a Kubernetes object.
+///
+/// Each change is `{"op": "add"|"remove"|"replace", "path": <JSON pointer>, "from"?, "to"?}`.
+/// Objects are compared field by field; arrays are compared as a whole.
+pub fn object_changes(current: &Value, proposed: &Value) -> Vec<Value> {
+    let mut changes = Vec::new();
+    diff_values("", &strip_noise(current), &strip_noise(proposed), &mut changes);
+    changes
+}
+
+fn strip_noise(object: &Value) -> Value {
+    let mut object = object.clone();
+    if let Some(metadata) = object.get_mut("metadata").and_then(Value::as_object_mut) {
+        for key in IGNORED_METADATA {
+            metadata.remove(*key);
+        }
+    }
+    object
+}
+
+fn diff_values(path: &str, before: &Value, after: &Value, changes: &mut Vec<Value>) {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => diff_objects(path, before, after, changes),
+        _ if before == after => {}
+        _ => changes.push(json!({ "op": "replace", "path": path, "from": before, "to": after })),
+    }
+}
+
+fn diff_objects(path: &str, before: &Map<String, Value>, after: &Map<String, Value>, changes: &mut Vec<Value>) {
+    for (key, old) in before {
+        let child = format!("{}/{}", path, escape_pointer(key));
+        match after.get(key) {
+            Some(new) => diff_values(&child, old, new, changes),
+            None => changes.push(json!({ "op": "remove", "path": child, "from": old })),
+        }
+    }
+
+    for (key, new) in after {
+        if !before.contains_key(key) {
+            let child = format!("{}/{}", path, escape_pointer(key));
+            changes.push(json!({ "op": "add", "path": child, "to": new }));
+        }
+    }
+}
+
+/// Builds a JSON merge patch (RFC 7386) that turns `before` into `after`.
+///
+/// Only changed fields are included, and removed fields are set to `null`; arrays are replaced as a whole.
+pub fn merge_patch(before: &Value, after: &Value) -> Value {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            let mut patch = Map::new();
+            for key in before.keys().filter(|key| !after.contains_key(*key)) {
+                patch.insert(key.clone(), Value::Null);
+            }
+            for (key, new) in after {
+                match before.get(key) {
+                    Some(old) if old == new => {}
+                    Some(old @ Value::Object(_)) if new.is_object() => {
+                        patch.insert(key.clone(), merge_patch(old, new));
+                    }
+                    _ => {
+                        patch.insert(key.clone(), new.clone());
+                    }
+                }
+            }
+            Value::Object(patch)
+        }
+        _ => after.clone(),
+    }
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_patch() {
+        let before = json!({ "instances": 1, "postgresql": { "parameters": { "a": "1" }, "pg_ident": ["m u p"] } });
+        let after = json!({ "instances": 3, "postgresql": { "parameters": { "a": "1" } }, "stopDelay": 60 });
+
+        assert_eq!(
+            merge_patch(&before, &after),
+            json!({ "instances": 3, "postgresql": { "pg_ident": null }, "stopDelay": 60 })
+        );
+        assert_eq!(merge_patch(&before, &before), json!({}));
+    }
+
+    #[test]
+    fn test_object_changes() {
+        let current = json!({
+            "metadata": { "name": "db", "resourceVersion": "1", "labels": { "app.kubernetes.io/name": "db" } },
+            "spec": { "instances": 1, "storage": { "size": "1Gi" }, "monitoring": { "enablePodMonitor": false } }
+        });
+        let proposed = json!({
+            "metadata": { "name": "db", "resourceVersion": "2", "labels": { "app.kubernetes.io/name": "pg" } },
+            "spec": { "instances": 3, "storage": { "size": "1Gi" }, "imageName": "postgres:16" }
+        });
+
+        let changes = object_changes(&current, &proposed);
+
+        assert_eq!(
+            changes,
+            vec![
+                json!({ "op": "replace", "path": "/metadata/labels/app.kubernetes.io~1name", "from": "db", "to": "pg" }),
+                json!({ "op": "replace", "path": "/spec/instances", "from": 1, "to": 3 }),
+                json!({ "op": "remove", "path": "/spec/monitoring", "from": { "enablePodMonitor": false } }),
+                json!({ "op": "add", "path": "/spec/imageName", "to": "postgres:16" }),
+            ]
+        );
+        assert!(object_changes(&current, &current).is_empty());
+    }
+}
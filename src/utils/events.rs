@@ -0,0 +1,72 @@
+use crate::config;
+use crate::resources::FIELD_MANAGER;
+use k8s_openapi::api::core::v1::ObjectReference;
+use kube::runtime::events::{Event, EventType, Recorder, Reporter};
+use kube::{Client, Resource};
+
+/// Reference to an object by type, namespace and name, for events about objects that may not exist yet.
+pub fn reference<K: Resource<DynamicType = ()>>(namespace: &str, name: &str) -> ObjectReference {
+    ObjectReference {
+        api_version: Some(K::api_version(&()).into_owned()),
+        kind: Some(K::kind(&()).into_owned()),
+        namespace: Some(namespace.to_string()),
+        name: Some(name.to_string()),
+        ..Default::default()
+    }
+}
+
+pub fn normal(reason: &str, action: &str, note: String) -> Event {
+    event(EventType::Normal, reason, action, note)
+}
+
+pub fn warning(reason: &str, action: &str, note: String) -> Event {
+    event(EventType::Warning, reason, action, note)
+}
+
+fn event(type_: EventType, reason: &str, action: &str, note: String) -> Event {
+    Event {
+        type_,
+        reason: reason.to_string(),
+        note: Some(note),
+        action: action.to_string(),
+        secondary: None,
+    }
+}
+
+/// Records `event` on the referenced object when `EMIT_EVENTS` is set.
+///
+/// Events are sent in the background and failures (e.g. missing RBAC for `events.k8s.io`)
+/// are only logged, so they never delay or fail the operation they describe.
+pub fn publish(client: &Client, reference: ObjectReference, event: Event) {
+    let config = config::get();
+    if !config.emit_events {
+        return;
+    }
+
+    let reporter = Reporter {
+        controller: FIELD_MANAGER.to_string(),
+        instance: config.event_instance.clone(),
+    };
+    let recorder = Recorder::new(client.clone(), reporter, reference);
+    tokio::spawn(async move {
+        let reason = event.reason.clone();
+        if let Err(e) = recorder.publish(event).await {
+            tracing::warn!("Failed to record {} event: {}", reason, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::kubeflow::Notebook;
+
+    #[test]
+    fn test_reference() {
+        let reference = reference::<Notebook>("team-a", "nb");
+        assert_eq!(reference.api_version.as_deref(), Some("kubeflow.org/v1"));
+        assert_eq!(reference.kind.as_deref(), Some("Notebook"));
+        assert_eq!(reference.namespace.as_deref(), Some("team-a"));
+        assert_eq!(reference.name.as_deref(), Some("nb"));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::error::{AppError, Result};
+use axum::http::{uri::PathAndQuery, Method, Request, Uri};
+use std::future::Future;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+/// How the API server treats unknown or duplicate fields in a write (`fieldValidation`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldValidation {
+    /// Reject the write.
+    Strict,
+    /// Accept the write and return a warning per field.
+    #[default]
+    Warn,
+    /// Drop the fields silently.
+    Ignore,
+}
+
+impl FieldValidation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Strict => "Strict",
+            Self::Warn => "Warn",
+            Self::Ignore => "Ignore",
+        }
+    }
+
+    /// Parses a `?field_validation=` query parameter, `Warn` when absent.
+    pub fn from_query(value: Option<&str>) -> Result<Self> {
+        value.map(str::parse).transpose().map(Option::unwrap_or_default)
+    }
+}
+
+impl FromStr for FieldValidation {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "strict" => Ok(Self::Strict),
+            "warn" => Ok(Self::Warn),
+            "ignore" => Ok(Self::Ignore),
+            _ => Err(AppError::BadRequest(format!(
+                "Invalid field_validation '{}': expected strict, warn or ignore",
+                value
+            ))),
+        }
+    }
+}
+
+tokio::task_local! {
+    static FIELD_VALIDATION: FieldValidation;
+}
+
+/// Runs `future` with `mode` applied to the Kubernetes writes made within it.
+pub async fn scope<F: Future>(mode: FieldValidation, future: F) -> F::Output {
+    FIELD_VALIDATION.scope(mode, future).await
+}
+
+fn current() -> FieldValidation {
+    FIELD_VALIDATION.try_with(|mode| *mode).unwrap_or_default()
+}
+
+/// Adds `fieldValidation` to the query string of a request URI, unless it is already set.
+fn with_field_validation(uri: &Uri, mode: FieldValidation) -> Option<Uri> {
+    let path_and_query = uri.path_and_query()?;
+    if path_and_query.query().is_some_and(|query| query.contains("fieldValidation=")) {
+        return None;
+    }
+
+    let separator = if path_and_query.query().is_some() { '&' } else { '?' };
+    let path_and_query: PathAndQuery = format!("{}{}fieldValidation={}", path_and_query, separator, mode.as_str())
+        .parse()
+        .ok()?;
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(path_and_query);
+    Uri::from_parts(parts).ok()
+}
+
+/// Client middleware that sets `fieldValidation` on every create, update and patch.
+///
+/// `PostParams` has no field for it, so it is added to the request URL instead.
+#[derive(Clone)]
+pub struct FieldValidationLayer;
+
+impl<S> Layer<S> for FieldValidationLayer {
+    type Service = FieldValidationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        FieldValidationService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct FieldValidationService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for FieldValidationService<S>
+where
+    S: Service<Request<ReqBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        let is_write = matches!(*request.method(), Method::POST | Method::PUT | Method::PATCH);
+        if is_write && let Some(uri) = with_field_validation(request.uri(), current()) {
+            *request.uri_mut() = uri;
+        }
+        self.inner.call(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use tower::ServiceExt;
+
+    async fn sent_uri(method: Method, uri: &str) -> String {
+        let service = FieldValidationLayer.layer(tower::service_fn(|request: Request<()>| async move {
+            Ok::<_, Infallible>(request.uri().to_string())
+        }));
+        let request = Request::builder().method(method).uri(uri).body(()).unwrap();
+        service.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_strict_write_sets_field_validation() {
+        let uri = scope(
+            FieldValidation::Strict,
+            sent_uri(Method::POST, "/apis/postgresql.cnpg.io/v1/namespaces/default/clusters?dryRun=All"),
+        )
+        .await;
+        assert_eq!(uri, "/apis/postgresql.cnpg.io/v1/namespaces/default/clusters?dryRun=All&fieldValidation=Strict");
+
+        // Warn by default, and reads are left alone
+        assert_eq!(sent_uri(Method::PUT, "/api/v1/x").await, "/api/v1/x?fieldValidation=Warn");
+        assert_eq!(sent_uri(Method::GET, "/api/v1/x").await, "/api/v1/x");
+    }
+
+    #[test]
+    fn test_parse_field_validation() {
+        assert_eq!(FieldValidation::from_query(None).unwrap(), FieldValidation::Warn);
+        assert_eq!(FieldValidation::from_query(Some("Strict")).unwrap(), FieldValidation::Strict);
+        assert!(FieldValidation::from_query(Some("loose")).is_err());
+    }
+}
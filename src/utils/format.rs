@@ -0,0 +1,261 @@
+use crate::config;
+use crate::error::{AppError, Result};
+use crate::models::OperationResponse;
+use crate::utils::warnings;
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{FromRequest, FromRequestParts, Json, Request},
+    http::{header, request::Parts, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json as ResponseJson, Response},
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::convert::Infallible;
+
+const YAML_MEDIA_TYPES: &[&str] = &["application/yaml", "application/x-yaml", "text/yaml"];
+const NDJSON_MEDIA_TYPES: &[&str] = &["application/x-ndjson", "application/ndjson"];
+
+/// Response serialization negotiated from the `Accept` header. JSON unless YAML or NDJSON is asked for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    Yaml,
+    /// Newline-delimited JSON; list endpoints stream one item per line.
+    Ndjson,
+}
+
+impl ResponseFormat {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let accept = match headers.get(header::ACCEPT).and_then(|value| value.to_str().ok()) {
+            Some(accept) => accept,
+            None => return Self::Json,
+        };
+
+        // First acceptable entry wins; entries with q=0 are explicitly refused
+        for entry in accept.split(',') {
+            let mut parts = entry.split(';').map(str::trim);
+            let media_type = parts.next().unwrap_or("").to_ascii_lowercase();
+            let refused = parts.any(|param| param.replace(' ', "") == "q=0");
+
+            if refused {
+                continue;
+            }
+            if YAML_MEDIA_TYPES.contains(&media_type.as_str()) {
+                return Self::Yaml;
+            }
+            if NDJSON_MEDIA_TYPES.contains(&media_type.as_str()) {
+                return Self::Ndjson;
+            }
+            if media_type == "application/json" || media_type == "*/*" || media_type == "application/*" {
+                return Self::Json;
+            }
+        }
+
+        Self::Json
+    }
+
+    pub fn render(self, value: Value) -> Result<Response> {
+        match self {
+            Self::Json => Ok(ResponseJson(value).into_response()),
+            Self::Yaml => {
+                let body = serde_yaml::to_string(&value)?;
+                Ok((
+                    [(header::CONTENT_TYPE, HeaderValue::from_static("application/yaml"))],
+                    body,
+                )
+                    .into_response())
+            }
+            Self::Ndjson => {
+                let mut body = serde_json::to_vec(&value)?;
+                body.push(b'\n');
+                Ok((
+                    [(header::CONTENT_TYPE, HeaderValue::from_static("application/x-ndjson"))],
+                    body,
+                )
+                    .into_response())
+            }
+        }
+    }
+}
+
+/// Request body deserialized from JSON, or from YAML when `Content-Type` is a YAML media type.
+///
+/// JSON bodies go through axum's `Json` extractor unchanged, rejections included.
+#[derive(Debug)]
+pub struct Payload<T>(pub T);
+
+/// Whether a `Content-Type` value names one of the YAML media types.
+pub fn is_yaml(content_type: &str) -> bool {
+    let media_type = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    YAML_MEDIA_TYPES.contains(&media_type.as_str())
+}
+
+#[async_trait]
+impl<T, S> FromRequest<S> for Payload<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> std::result::Result<Self, Self::Rejection> {
+        let yaml = request
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(is_yaml);
+
+        if !yaml {
+            let Json(value) = Json::<T>::from_request(request, state).await.map_err(IntoResponse::into_response)?;
+            return Ok(Self(value));
+        }
+
+        let body = Bytes::from_request(request, state).await.map_err(IntoResponse::into_response)?;
+        serde_yaml::from_slice(&body)
+            .map(Self)
+            .map_err(|e| AppError::BadRequest(format!("Failed to parse the YAML request body: {}", e)).into_response())
+    }
+}
+
+/// Body of a mutating endpoint's response: `result` in an `OperationResponse` envelope together with the
+/// request's warnings, or with `LEGACY_RESPONSES` the bare result with the warnings attached.
+pub fn operation(resource_type: &str, mut result: Value) -> Value {
+    if config::get().legacy_responses {
+        warnings::attach(&mut result);
+        return result;
+    }
+
+    serde_json::json!(OperationResponse {
+        success: true,
+        resource_type: resource_type.to_string(),
+        data: result,
+        warnings: warnings::take(),
+    })
+}
+
+/// Result of a server-side dry run: the object the server would have stored, marked as not persisted.
+pub fn dry_run(namespace: &str, name: &str, object: Value) -> Value {
+    serde_json::json!({
+        "dry_run": true,
+        "name": name,
+        "namespace": namespace,
+        "object": object
+    })
+}
+
+/// Response to a create: `201 Created` with the new resource's URL in `Location`.
+///
+/// When `if_not_exists` found the resource already there (`"created": false`), the status is `200 OK`;
+/// `Location` still points at it.
+pub fn created(format: ResponseFormat, collection_path: &str, namespace: &str, name: &str, body: Value) -> Result<Response> {
+    let created = body.pointer("/data/created").or_else(|| body.get("created"));
+    let status = if created == Some(&Value::Bool(false)) {
+        StatusCode::OK
+    } else {
+        StatusCode::CREATED
+    };
+    let location = format!("{}{}/{}/{}", config::get().api_prefix, collection_path, namespace, name);
+
+    let mut response = format.render(body)?;
+    *response.status_mut() = status;
+    if let Ok(location) = HeaderValue::from_str(&location) {
+        response.headers_mut().insert(header::LOCATION, location);
+    }
+    Ok(response)
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ResponseFormat {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> std::result::Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accept(value: &str) -> ResponseFormat {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_str(value).unwrap());
+        ResponseFormat::from_headers(&headers)
+    }
+
+    #[test]
+    fn test_response_format_negotiation() {
+        assert_eq!(ResponseFormat::from_headers(&HeaderMap::new()), ResponseFormat::Json);
+        assert_eq!(accept("application/json"), ResponseFormat::Json);
+        assert_eq!(accept("application/yaml"), ResponseFormat::Yaml);
+        assert_eq!(accept("text/yaml, application/json"), ResponseFormat::Yaml);
+        assert_eq!(accept("application/json, application/yaml"), ResponseFormat::Json);
+        assert_eq!(accept("application/yaml;q=0, */*"), ResponseFormat::Json);
+        assert_eq!(accept("text/html"), ResponseFormat::Json);
+        assert_eq!(accept("application/x-ndjson"), ResponseFormat::Ndjson);
+    }
+
+    #[test]
+    fn test_created_response() {
+        let response = created(ResponseFormat::Json, "/cnpg/clusters", "team-a", "db", serde_json::json!({ "name": "db" })).unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[header::LOCATION], "/cnpg/clusters/team-a/db");
+
+        let existing = created(ResponseFormat::Json, "/cnpg/clusters", "team-a", "db", serde_json::json!({ "created": false })).unwrap();
+        assert_eq!(existing.status(), StatusCode::OK);
+        assert_eq!(existing.headers()[header::LOCATION], "/cnpg/clusters/team-a/db");
+
+        let wrapped = operation("cnpg-cluster", serde_json::json!({ "created": false }));
+        let wrapped = created(ResponseFormat::Yaml, "/cnpg/clusters", "team-a", "db", wrapped).unwrap();
+        assert_eq!(wrapped.status(), StatusCode::OK);
+        assert_eq!(wrapped.headers()[header::CONTENT_TYPE], "application/yaml");
+    }
+
+    #[tokio::test]
+    async fn test_yaml_payload() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Spec {
+            name: String,
+            instances: i32,
+        }
+
+        let request = Request::builder()
+            .header(header::CONTENT_TYPE, "application/yaml")
+            .body(axum::body::Body::from("name: db\ninstances: 3\n"))
+            .unwrap();
+        let Payload(spec) = Payload::<Spec>::from_request(request, &()).await.unwrap();
+        assert_eq!(spec.name, "db");
+        assert_eq!(spec.instances, 3);
+
+        let request = Request::builder()
+            .header(header::CONTENT_TYPE, "text/yaml")
+            .body(axum::body::Body::from("name: [db"))
+            .unwrap();
+        let rejection = Payload::<Spec>::from_request(request, &()).await.unwrap_err();
+        assert_eq!(rejection.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_operation_envelope() {
+        let body = warnings::scope(async {
+            warnings::push("max_wal_senders is too low".to_string());
+            operation("cnpg-cluster", serde_json::json!({ "name": "db" }))
+        })
+        .await;
+
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "success": true,
+                "resource_type": "cnpg-cluster",
+                "data": { "name": "db" },
+                "warnings": ["max_wal_senders is too low"]
+            })
+        );
+
+        let body = warnings::scope(async { operation("cnpg-cluster", serde_json::json!({ "name": "db" })) }).await;
+        assert!(body.get("warnings").is_none());
+    }
+}
@@ -0,0 +1,15 @@
+use crate::config;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::LazyLock;
+
+static READ_ONLY: LazyLock<AtomicBool> = LazyLock::new(|| AtomicBool::new(config::get().read_only));
+
+/// Whether mutations are currently rejected. Starts from `READ_ONLY` and follows `set_read_only`.
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::SeqCst)
+}
+
+/// Switches read-only mode on or off and returns the previous setting.
+pub fn set_read_only(enabled: bool) -> bool {
+    READ_ONLY.swap(enabled, Ordering::SeqCst)
+}
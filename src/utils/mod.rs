@@ -1,4 +1,20 @@
+pub mod batch;
+pub mod coalesce;
+pub mod concurrency;
+pub mod diff;
+pub mod events;
+pub mod field_validation;
+pub mod format;
+pub mod maintenance;
+pub mod namespace;
+pub mod openapi;
+pub mod pagination;
+pub mod quantity;
+pub mod supervisor;
+pub mod timeout;
 pub mod validation;
+pub mod warnings;
+pub mod watch;
 
 // Future utilities for common operations
 // e.g., YAML generation, etc.
\ No newline at end of file
@@ -0,0 +1,153 @@
+use crate::config;
+use crate::error::{AppError, Result};
+use crate::utils::{validation, warnings};
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{request::Parts, HeaderMap, HeaderName},
+};
+use k8s_openapi::api::core::v1::Namespace;
+use kube::{Api, Client};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// Target namespace set by a gateway, for endpoints without a namespace in the path.
+pub const NAMESPACE_HEADER: HeaderName = HeaderName::from_static("x-namespace");
+
+/// How long a namespace's labels are reused before they are read again.
+const LABEL_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Namespace labels and when they were read, by namespace.
+type LabelCache = HashMap<String, (Instant, BTreeMap<String, String>)>;
+
+static LABEL_CACHE: LazyLock<Mutex<LabelCache>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The validated `X-Namespace` header, if the request has one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderNamespace(pub Option<String>);
+
+impl HeaderNamespace {
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self> {
+        let Some(value) = headers.get(NAMESPACE_HEADER) else {
+            return Ok(Self(None));
+        };
+
+        let namespace = value
+            .to_str()
+            .map_err(|_| AppError::BadRequest("X-Namespace header is not valid text".to_string()))?
+            .trim();
+        validation::validate_namespace(namespace)?;
+
+        Ok(Self(Some(namespace.to_string())))
+    }
+
+    /// Combines the header with the namespace from the body or query (`source`). Either may be omitted,
+    /// but when both are given they must agree.
+    pub fn resolve(&self, explicit: Option<String>, source: &str) -> Result<Option<String>> {
+        match (&self.0, explicit) {
+            (Some(header), Some(explicit)) if *header != explicit => Err(AppError::BadRequest(format!(
+                "X-Namespace header '{}' does not match {} namespace '{}'",
+                header, source, explicit
+            ))),
+            (header, explicit) => Ok(explicit.or_else(|| header.clone())),
+        }
+    }
+}
+
+/// Labels of the namespace listed in `PROPAGATE_NAMESPACE_LABELS`, to be copied onto resources created in it.
+///
+/// Namespace labels are cached for a short while. Without permission to read the namespace nothing is
+/// propagated and a warning is returned with the response.
+pub async fn propagated_labels(client: &Client, namespace: &str) -> Result<BTreeMap<String, String>> {
+    let Some(keys) = config::get().propagate_namespace_labels.as_deref() else {
+        return Ok(BTreeMap::new());
+    };
+
+    let cached = LABEL_CACHE
+        .lock()
+        .unwrap()
+        .get(namespace)
+        .filter(|(read_at, _)| read_at.elapsed() < LABEL_CACHE_TTL)
+        .map(|(_, labels)| labels.clone());
+    let labels = match cached {
+        Some(labels) => labels,
+        None => {
+            let api: Api<Namespace> = Api::all(client.clone());
+            let labels = match api.get_opt(namespace).await {
+                Ok(found) => found.and_then(|namespace| namespace.metadata.labels).unwrap_or_default(),
+                Err(kube::Error::Api(response)) if response.code == 403 => {
+                    warnings::push(format!(
+                        "Labels of namespace '{}' were not propagated: not permitted to read the namespace",
+                        namespace
+                    ));
+                    return Ok(BTreeMap::new());
+                }
+                Err(e) => return Err(e.into()),
+            };
+            LABEL_CACHE
+                .lock()
+                .unwrap()
+                .insert(namespace.to_string(), (Instant::now(), labels.clone()));
+            labels
+        }
+    };
+
+    Ok(select_labels(&labels, keys))
+}
+
+/// The entries of `labels` whose key is one of `keys`.
+fn select_labels(labels: &BTreeMap<String, String>, keys: &[String]) -> BTreeMap<String, String> {
+    labels
+        .iter()
+        .filter(|(key, _)| keys.contains(key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for HeaderNamespace {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> std::result::Result<Self, Self::Rejection> {
+        Self::from_headers(&parts.headers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_header_namespace_resolution() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(HeaderNamespace::from_headers(&headers).unwrap(), HeaderNamespace(None));
+
+        headers.insert(NAMESPACE_HEADER, HeaderValue::from_static("team-a"));
+        let header = HeaderNamespace::from_headers(&headers).unwrap();
+
+        assert_eq!(header.resolve(None, "body").unwrap().as_deref(), Some("team-a"));
+        assert_eq!(header.resolve(Some("team-a".to_string()), "body").unwrap().as_deref(), Some("team-a"));
+        assert!(matches!(header.resolve(Some("team-b".to_string()), "query"), Err(AppError::BadRequest(_))));
+        assert_eq!(HeaderNamespace(None).resolve(Some("team-b".to_string()), "query").unwrap().as_deref(), Some("team-b"));
+
+        headers.insert(NAMESPACE_HEADER, HeaderValue::from_static("Team_A"));
+        assert!(HeaderNamespace::from_headers(&headers).is_err());
+    }
+
+    #[test]
+    fn test_select_labels() {
+        let labels = BTreeMap::from([
+            ("tenant".to_string(), "acme".to_string()),
+            ("cost-center".to_string(), "42".to_string()),
+            ("kubernetes.io/metadata.name".to_string(), "team-a".to_string()),
+        ]);
+        let keys = vec!["tenant".to_string(), "cost-center".to_string(), "missing".to_string()];
+
+        let selected = select_labels(&labels, &keys);
+        assert_eq!(selected.len(), 2);
+        assert_eq!(selected["tenant"], "acme");
+        assert_eq!(selected["cost-center"], "42");
+    }
+}
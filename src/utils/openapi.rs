@@ -0,0 +1,202 @@
+//! OpenAPI 3 description of the API, with schemas generated from the models' `JsonSchema` derives.
+
+use schemars::r#gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+
+/// Collects operations and the schemas they reference into an OpenAPI document.
+pub struct ApiDoc {
+    generator: SchemaGenerator,
+    paths: Map<String, Value>,
+}
+
+/// An operation being described; see `ApiDoc::operation`.
+pub struct Operation<'a> {
+    generator: &'a mut SchemaGenerator,
+    value: &'a mut Value,
+}
+
+impl ApiDoc {
+    pub fn new() -> Self {
+        Self {
+            generator: SchemaSettings::openapi3().into_generator(),
+            paths: Map::new(),
+        }
+    }
+
+    /// Adds an operation on an axum route; its `:param` segments become path parameters.
+    pub fn operation(&mut self, method: &str, path: &str, summary: &str) -> Operation<'_> {
+        let (path, parameters) = openapi_path(path);
+        let item = self.paths.entry(path).or_insert_with(|| json!({}));
+        item[method] = json!({
+            "summary": summary,
+            "parameters": parameters,
+            "responses": {
+                "default": {
+                    "description": "Error",
+                    "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+                }
+            }
+        });
+
+        Operation {
+            generator: &mut self.generator,
+            value: &mut item[method],
+        }
+    }
+
+    /// The OpenAPI document, served below `api_prefix`.
+    pub fn into_spec(self, api_prefix: &str) -> Value {
+        let mut schemas: Map<String, Value> = self
+            .generator
+            .definitions()
+            .iter()
+            .map(|(name, schema)| (name.clone(), serde_json::to_value(schema).unwrap_or_default()))
+            .collect();
+        schemas.insert("Error".to_string(), error_schema());
+
+        json!({
+            "openapi": "3.0.3",
+            "info": {
+                "title": "K8s Resource Manager",
+                "version": env!("CARGO_PKG_VERSION")
+            },
+            "servers": [{ "url": if api_prefix.is_empty() { "/" } else { api_prefix } }],
+            "paths": self.paths,
+            "components": { "schemas": schemas }
+        })
+    }
+}
+
+impl Operation<'_> {
+    /// Request body, accepted as JSON or YAML.
+    pub fn body<T: JsonSchema>(self) -> Self {
+        let schema = schema_value(self.generator.subschema_for::<T>());
+        self.value["requestBody"] = json!({
+            "required": true,
+            "content": {
+                "application/json": { "schema": schema },
+                "application/yaml": { "schema": schema }
+            }
+        });
+        self
+    }
+
+    /// Query parameters, one per field of `T`.
+    pub fn query<T: JsonSchema>(self) -> Self {
+        let root = self.generator.root_schema_for::<T>();
+        let Some(object) = root.schema.object else {
+            return self;
+        };
+        if let Some(parameters) = self.value["parameters"].as_array_mut() {
+            for (name, schema) in object.properties {
+                let mut schema = schema_value(schema);
+                let description = schema.as_object_mut().and_then(|schema| schema.remove("description"));
+                let mut parameter = json!({
+                    "name": name,
+                    "in": "query",
+                    "required": object.required.contains(&name),
+                    "schema": schema
+                });
+                if let Some(description) = description {
+                    parameter["description"] = description;
+                }
+                parameters.push(parameter);
+            }
+        }
+        self
+    }
+
+    /// Successful response body.
+    pub fn returns<T: JsonSchema>(self) -> Self {
+        let schema = schema_value(self.generator.subschema_for::<T>());
+        self.value["responses"]["200"] = json!({
+            "description": "Success",
+            "content": { "application/json": { "schema": schema } }
+        });
+        self
+    }
+}
+
+impl Default for ApiDoc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn schema_value(schema: Schema) -> Value {
+    serde_json::to_value(schema).unwrap_or_default()
+}
+
+/// Converts `/clusters/:namespace/:name` to `/clusters/{namespace}/{name}` and lists its parameters.
+fn openapi_path(path: &str) -> (String, Vec<Value>) {
+    let mut parameters = Vec::new();
+    let segments: Vec<String> = path
+        .split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(name) => {
+                parameters.push(json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" }
+                }));
+                format!("{{{}}}", name)
+            }
+            None => segment.to_string(),
+        })
+        .collect();
+    (segments.join("/"), parameters)
+}
+
+/// Body of every error response (see `AppError::into_body`).
+fn error_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "error": {
+                "type": "object",
+                "properties": {
+                    "type": { "type": "string" },
+                    "message": { "type": "string" },
+                    "status": { "type": "integer" },
+                    "request_id": { "type": "string", "nullable": true },
+                    "details": { "type": "object" }
+                },
+                "required": ["type", "message", "status"]
+            }
+        },
+        "required": ["error"]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::cnpg::ScaleClusterRequest;
+    use crate::models::DeleteQuery;
+
+    #[test]
+    fn test_api_doc() {
+        let mut doc = ApiDoc::new();
+        doc.operation("put", "/cnpg/clusters/:namespace/:name/scale", "Scale a cluster")
+            .body::<ScaleClusterRequest>();
+        doc.operation("delete", "/cnpg/clusters/:namespace/:name", "Delete a cluster")
+            .query::<DeleteQuery>();
+        let spec = doc.into_spec("/api/v1");
+
+        assert_eq!(spec["servers"][0]["url"], "/api/v1");
+        let scale = &spec["paths"]["/cnpg/clusters/{namespace}/{name}/scale"]["put"];
+        assert_eq!(scale["parameters"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            scale["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/ScaleClusterRequest"
+        );
+        assert!(spec["components"]["schemas"]["ScaleClusterRequest"].is_object());
+
+        let delete = &spec["paths"]["/cnpg/clusters/{namespace}/{name}"]["delete"];
+        let names: Vec<&str> = delete["parameters"].as_array().unwrap().iter().filter_map(|p| p["name"].as_str()).collect();
+        assert_eq!(names, ["namespace", "name", "propagation_policy", "soft"]);
+    }
+}
@@ -0,0 +1,378 @@
+use crate::config;
+use crate::error::{AppError, Result};
+use crate::models::ListQuery;
+use crate::resources::ListOptions;
+use crate::utils::validation;
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use futures::stream;
+use k8s_openapi::chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
+
+/// Page size used when streaming a list and the caller set no `limit`.
+const STREAM_PAGE_SIZE: u32 = 100;
+
+/// Opaque pagination cursor handed to clients in place of the raw Kubernetes continue token.
+///
+/// The cursor carries everything needed to fetch the next page, so clients only have to
+/// pass back a single string and the service holds no per-client state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub namespace: String,
+    #[serde(rename = "continue")]
+    pub continue_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label_selector: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> Result<String> {
+        let raw = serde_json::to_vec(self)?;
+        Ok(URL_SAFE_NO_PAD.encode(raw))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let raw = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| AppError::BadRequest("Invalid pagination cursor".to_string()))?;
+
+        serde_json::from_slice(&raw)
+            .map_err(|_| AppError::BadRequest("Invalid pagination cursor".to_string()))
+    }
+}
+
+/// Resolves the target namespace and list options for a list request.
+///
+/// When a cursor is supplied its namespace wins, but an explicit conflicting
+/// `namespace` query parameter is rejected rather than silently ignored.
+/// A raw `continue_token` is passed through as is, so the caller must repeat the
+/// namespace and filters of the first page.
+pub fn resolve_list_query(query: &ListQuery) -> Result<(String, ListOptions)> {
+    if query.cursor.is_some() && query.continue_token.is_some() {
+        return Err(AppError::BadRequest(
+            "cursor and continue_token cannot be combined".to_string(),
+        ));
+    }
+
+    let mut options = ListOptions {
+        limit: page_size(query.limit, config::get().max_page_size)?,
+        created_after: parse_timestamp("created_after", query.created_after.as_deref())?,
+        created_before: parse_timestamp("created_before", query.created_before.as_deref())?,
+        ..Default::default()
+    };
+
+    let namespace = match query.cursor.as_deref() {
+        Some(encoded) => {
+            let cursor = Cursor::decode(encoded)?;
+
+            if let Some(ref namespace) = query.namespace
+                && *namespace != cursor.namespace
+            {
+                return Err(AppError::BadRequest(format!(
+                    "Cursor was issued for namespace '{}', not '{}'",
+                    cursor.namespace, namespace
+                )));
+            }
+
+            if let Some(ref selector) = query.label_selector
+                && cursor.label_selector.as_ref() != Some(selector)
+            {
+                return Err(AppError::BadRequest(
+                    "Cursor was issued for a different label_selector".to_string(),
+                ));
+            }
+
+            options.continue_token = Some(cursor.continue_token);
+            options.label_selector = cursor.label_selector;
+            options.created_after = options.created_after.or(cursor.created_after);
+            options.created_before = options.created_before.or(cursor.created_before);
+            cursor.namespace
+        }
+        None => {
+            options.continue_token = query.continue_token.clone();
+            options.label_selector = query.label_selector.clone();
+            query.namespace.clone().unwrap_or_else(|| config::get().default_namespace.clone())
+        }
+    };
+
+    if let Some(ref selector) = options.label_selector {
+        validation::validate_label_selector(selector)?;
+    }
+
+    validation::validate_namespace(&namespace)?;
+
+    if let (Some(after), Some(before)) = (options.created_after, options.created_before)
+        && after >= before
+    {
+        return Err(AppError::BadRequest(
+            "created_after must be earlier than created_before".to_string(),
+        ));
+    }
+
+    Ok((namespace, options))
+}
+
+/// The `limit` passed to the API server: the requested one, clamped to `MAX_PAGE_SIZE`.
+fn page_size(limit: Option<u32>, max_page_size: u32) -> Result<Option<u32>> {
+    match limit {
+        Some(0) => Err(AppError::BadRequest("limit must be greater than 0".to_string())),
+        limit => Ok(limit.map(|limit| limit.min(max_page_size))),
+    }
+}
+
+/// Parses an optional RFC 3339 query parameter.
+fn parse_timestamp(field: &str, value: Option<&str>) -> Result<Option<DateTime<Utc>>> {
+    value
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .map_err(|e| {
+                    AppError::BadRequest(format!(
+                        "Invalid {} '{}': expected an RFC 3339 timestamp ({})",
+                        field, value, e
+                    ))
+                })
+        })
+        .transpose()
+}
+
+/// Adds the opaque `cursor` for the next page to a list result, next to the raw Kubernetes
+/// token as a top-level `continue` (`null` on the last page).
+///
+/// The effective `limit` is added too, so clients can tell when theirs was clamped, and the
+/// API server's estimate of the items after this page as `remaining_item_count` (`null` when
+/// it gave none, e.g. on the last page or with a label selector).
+pub fn attach_cursor(result: &mut Value, namespace: &str, options: &ListOptions) -> Result<()> {
+    let token = continue_token(result);
+    let remaining_item_count = result
+        .get("remaining_item_count")
+        .or_else(|| result.pointer("/metadata/remainingItemCount"))
+        .cloned()
+        .unwrap_or(Value::Null);
+    let cursor = match token.clone() {
+        Some(continue_token) => Some(
+            Cursor {
+                namespace: namespace.to_string(),
+                continue_token,
+                label_selector: options.label_selector.clone(),
+                created_after: options.created_after,
+                created_before: options.created_before,
+            }
+            .encode()?,
+        ),
+        None => None,
+    };
+
+    if let Some(metadata) = result.get_mut("metadata").and_then(|m| m.as_object_mut()) {
+        metadata.remove("continue");
+    }
+
+    if let Some(object) = result.as_object_mut() {
+        object.insert("continue".to_string(), token.into());
+        object.insert("cursor".to_string(), cursor.into());
+        object.insert("remaining_item_count".to_string(), remaining_item_count);
+        if let Some(limit) = options.limit {
+            object.insert("limit".to_string(), limit.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Streams every item of a list as newline-delimited JSON, fetching one page at a time.
+///
+/// The first page is fetched before the response starts so that errors such as a missing
+/// namespace still produce a normal error response. Each later page is written as one chunk.
+pub async fn stream_ndjson<F, Fut>(mut options: ListOptions, fetch_page: F) -> Result<Response>
+where
+    F: Fn(ListOptions) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = Result<Value>> + Send + 'static,
+{
+    options.limit.get_or_insert(STREAM_PAGE_SIZE);
+    let first_page = fetch_page(options.clone()).await?;
+
+    // State: the already fetched page (if any) and the options it was listed with
+    let pages = stream::try_unfold(Some((Some(first_page), options)), move |state| {
+        let fetch_page = fetch_page.clone();
+        async move {
+            let Some((fetched, options)) = state else {
+                return Ok(None);
+            };
+
+            let page = match fetched {
+                Some(page) => page,
+                None => fetch_page(options.clone()).await?,
+            };
+
+            let chunk = ndjson_lines(&page)?;
+            let next = continue_token(&page).map(|token| {
+                let mut options = options;
+                options.continue_token = Some(token);
+                (None, options)
+            });
+
+            Ok::<_, AppError>(Some((chunk, next)))
+        }
+    });
+
+    Ok((
+        [(header::CONTENT_TYPE, HeaderValue::from_static("application/x-ndjson"))],
+        Body::from_stream(pages),
+    )
+        .into_response())
+}
+
+/// Serializes the items of a list page (`items` or `resources`), one JSON object per line.
+fn ndjson_lines(page: &Value) -> Result<Bytes> {
+    let items = page
+        .get("items")
+        .or_else(|| page.get("resources"))
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let mut chunk = Vec::new();
+    for item in items {
+        serde_json::to_writer(&mut chunk, item)?;
+        chunk.push(b'\n');
+    }
+    Ok(Bytes::from(chunk))
+}
+
+/// The Kubernetes continue token of a list result, if there are more pages.
+fn continue_token(result: &Value) -> Option<String> {
+    result
+        .get("continue")
+        .or_else(|| result.pointer("/metadata/continue"))
+        .and_then(|token| token.as_str())
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor {
+            namespace: "team-a".to_string(),
+            continue_token: "eyJ2IjoibWV0YS5rOHMuaW8vdjEifQ".to_string(),
+            label_selector: Some("app=db".to_string()),
+            created_after: Some(Utc::now()),
+            created_before: None,
+        };
+
+        let encoded = cursor.encode().unwrap();
+        assert_eq!(Cursor::decode(&encoded).unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_invalid_cursor_is_bad_request() {
+        assert!(matches!(Cursor::decode("not a cursor!"), Err(AppError::BadRequest(_))));
+        assert!(matches!(Cursor::decode("bm90LWpzb24"), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_cursor_namespace_conflict() {
+        let encoded = Cursor {
+            namespace: "team-a".to_string(),
+            continue_token: "token".to_string(),
+            label_selector: None,
+            created_after: None,
+            created_before: None,
+        }
+        .encode()
+        .unwrap();
+
+        let query = ListQuery {
+            namespace: Some("team-b".to_string()),
+            limit: None,
+            cursor: Some(encoded),
+            continue_token: None,
+            label_selector: None,
+            created_after: None,
+            created_before: None,
+        };
+        assert!(matches!(resolve_list_query(&query), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_resolve_creation_window() {
+        let query = |after: &str, before: Option<&str>| ListQuery {
+            namespace: None,
+            limit: None,
+            cursor: None,
+            continue_token: None,
+            label_selector: None,
+            created_after: Some(after.to_string()),
+            created_before: before.map(str::to_string),
+        };
+
+        let (_, options) = resolve_list_query(&query("2024-01-01T00:00:00+01:00", None)).unwrap();
+        assert_eq!(options.created_after.unwrap().to_rfc3339(), "2023-12-31T23:00:00+00:00");
+
+        assert!(matches!(resolve_list_query(&query("2024-01-01", None)), Err(AppError::BadRequest(_))));
+        assert!(matches!(
+            resolve_list_query(&query("2024-02-01T00:00:00Z", Some("2024-01-01T00:00:00Z"))),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_page_size() {
+        assert_eq!(page_size(None, 500).unwrap(), None);
+        assert_eq!(page_size(Some(50), 500).unwrap(), Some(50));
+        assert_eq!(page_size(Some(10_000), 500).unwrap(), Some(500));
+        assert!(matches!(page_size(Some(0), 500), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_attach_cursor() {
+        let mut result = json!({ "resources": [], "continue": "token" });
+        attach_cursor(&mut result, "default", &ListOptions::default()).unwrap();
+
+        assert_eq!(result["continue"], "token");
+        assert!(result["remaining_item_count"].is_null());
+        let cursor = Cursor::decode(result["cursor"].as_str().unwrap()).unwrap();
+        assert_eq!(cursor.continue_token, "token");
+        assert!(result.get("limit").is_none());
+
+        let options = ListOptions {
+            limit: Some(500),
+            ..Default::default()
+        };
+        let mut result = json!({ "resources": [], "continue": "" });
+        attach_cursor(&mut result, "default", &options).unwrap();
+        assert_eq!(result["limit"], 500);
+
+        let mut result = json!({ "items": [], "metadata": { "continue": "next", "remainingItemCount": 1150 } });
+        attach_cursor(&mut result, "default", &ListOptions::default()).unwrap();
+        assert_eq!(result["continue"], "next");
+        assert_eq!(result["remaining_item_count"], 1150);
+
+        let mut result = json!({ "items": [], "metadata": { "continue": "" } });
+        attach_cursor(&mut result, "default", &ListOptions::default()).unwrap();
+        assert!(result["cursor"].is_null());
+        assert!(result["continue"].is_null());
+        assert!(result.pointer("/metadata/continue").is_none());
+    }
+
+    #[test]
+    fn test_ndjson_lines() {
+        let page = json!({ "resources": [{ "name": "a" }, { "name": "b" }], "continue": "" });
+        assert_eq!(ndjson_lines(&page).unwrap(), "{\"name\":\"a\"}\n{\"name\":\"b\"}\n");
+        assert!(ndjson_lines(&json!({ "items": [] })).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,137 @@
+//! Parsing and canonical formatting of Kubernetes CPU and memory quantities.
+
+/// Binary suffixes, largest first.
+const BINARY_SUFFIXES: &[(&str, u128)] = &[
+    ("Ei", 1 << 60),
+    ("Pi", 1 << 50),
+    ("Ti", 1 << 40),
+    ("Gi", 1 << 30),
+    ("Mi", 1 << 20),
+    ("Ki", 1 << 10),
+];
+
+/// Decimal suffixes, largest first. `K` is accepted on input as an alias for `k`.
+const DECIMAL_SUFFIXES: &[(&str, u128)] = &[
+    ("E", 1_000_000_000_000_000_000),
+    ("P", 1_000_000_000_000_000),
+    ("T", 1_000_000_000_000),
+    ("G", 1_000_000_000),
+    ("M", 1_000_000),
+    ("k", 1_000),
+];
+
+/// Parses a CPU quantity (`500m`, `1`, `2.5`) into millicores. Sub-millicore precision is rejected.
+pub fn parse_cpu_millis(cpu: &str) -> Option<u128> {
+    match cpu.strip_suffix('m') {
+        Some(millis) => scaled(millis, 1),
+        None => scaled(cpu, 1_000),
+    }
+}
+
+/// Formats millicores as whole cores when exact, else as millicores: `1000` → `1`, `1500` → `1500m`.
+pub fn format_cpu_millis(millis: u128) -> String {
+    if millis.is_multiple_of(1_000) {
+        (millis / 1_000).to_string()
+    } else {
+        format!("{}m", millis)
+    }
+}
+
+/// Parses a memory or storage quantity (`512Mi`, `1G`, `1.5Gi`, `1024`) into bytes.
+pub fn parse_bytes(quantity: &str) -> Option<u128> {
+    let (number, multiplier) = BINARY_SUFFIXES
+        .iter()
+        .chain(DECIMAL_SUFFIXES)
+        .chain(&[("K", 1_000)])
+        .find_map(|&(suffix, multiplier)| quantity.strip_suffix(suffix).map(|number| (number, multiplier)))
+        .unwrap_or((quantity, 1));
+
+    scaled(number, multiplier)
+}
+
+/// Formats bytes with the largest suffix that represents them exactly, trying binary suffixes
+/// first unless `prefer_decimal`: `1073741824` → `1Gi`, `1500000000` → `1500M`.
+pub fn format_bytes(bytes: u128, prefer_decimal: bool) -> String {
+    if bytes == 0 {
+        return "0".to_string();
+    }
+
+    let (first, second) = if prefer_decimal {
+        (DECIMAL_SUFFIXES, BINARY_SUFFIXES)
+    } else {
+        (BINARY_SUFFIXES, DECIMAL_SUFFIXES)
+    };
+
+    first
+        .iter()
+        .chain(second)
+        .find(|&&(_, multiplier)| bytes.is_multiple_of(multiplier))
+        .map(|&(suffix, multiplier)| format!("{}{}", bytes / multiplier, suffix))
+        .unwrap_or_else(|| bytes.to_string())
+}
+
+/// The canonical form of a CPU quantity, or `None` if it cannot be parsed exactly.
+pub fn normalize_cpu(cpu: &str) -> Option<String> {
+    parse_cpu_millis(cpu).map(format_cpu_millis)
+}
+
+/// The canonical form of a memory or storage quantity, or `None` if it cannot be parsed exactly.
+/// Values written with a decimal suffix stay decimal where possible.
+pub fn normalize_bytes(quantity: &str) -> Option<String> {
+    let decimal = quantity.ends_with(['k', 'K', 'M', 'G', 'T', 'P', 'E']);
+    parse_bytes(quantity).map(|bytes| format_bytes(bytes, decimal))
+}
+
+/// Multiplies a non-negative decimal number by `multiplier`, if the result is a whole number.
+fn scaled(number: &str, multiplier: u128) -> Option<u128> {
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    if !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let digits: u128 = format!("{}{}", whole, fraction).parse().ok()?;
+    let divisor = 10u128.checked_pow(u32::try_from(fraction.len()).ok()?)?;
+    let value = digits.checked_mul(multiplier)?;
+
+    (value % divisor == 0).then(|| value / divisor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_cpu() {
+        assert_eq!(normalize_cpu("1000m").as_deref(), Some("1"));
+        assert_eq!(normalize_cpu("1").as_deref(), Some("1"));
+        assert_eq!(normalize_cpu("1.5").as_deref(), Some("1500m"));
+        assert_eq!(normalize_cpu("250m").as_deref(), Some("250m"));
+        assert_eq!(normalize_cpu("0.0001"), None);
+        assert_eq!(normalize_cpu("abc"), None);
+    }
+
+    #[test]
+    fn test_normalize_bytes() {
+        assert_eq!(normalize_bytes("1024Mi").as_deref(), Some("1Gi"));
+        assert_eq!(normalize_bytes("1Gi").as_deref(), Some("1Gi"));
+        assert_eq!(normalize_bytes("1.5Gi").as_deref(), Some("1536Mi"));
+        assert_eq!(normalize_bytes("1000M").as_deref(), Some("1G"));
+        assert_eq!(normalize_bytes("1500M").as_deref(), Some("1500M"));
+        assert_eq!(normalize_bytes("2048K").as_deref(), Some("2048k"));
+        assert_eq!(normalize_bytes("1048576k").as_deref(), Some("1048576k"));
+        assert_eq!(normalize_bytes("2000000").as_deref(), Some("2M"));
+        assert_eq!(normalize_bytes("1073741824").as_deref(), Some("1Gi"));
+        assert_eq!(normalize_bytes("0.5"), None);
+        assert_eq!(normalize_bytes("1GB"), None);
+    }
+
+    #[test]
+    fn test_equivalent_values_normalize_identically() {
+        assert_eq!(normalize_bytes("1Gi"), normalize_bytes("1024Mi"));
+        assert_eq!(normalize_bytes("1Gi"), normalize_bytes("1048576Ki"));
+        assert_eq!(normalize_cpu("2"), normalize_cpu("2000m"));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::metrics::{BACKGROUND_TASK_RESTARTS, BACKGROUND_TASK_UP};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How a supervised task is treated when it stops.
+#[derive(Debug, Clone, Copy)]
+pub struct TaskOptions {
+    /// The service is not ready while the task is down.
+    pub critical: bool,
+    /// Start the task again, with exponential backoff, when it returns or panics.
+    pub restart: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TaskState {
+    critical: bool,
+    alive: bool,
+}
+
+static TASKS: LazyLock<Mutex<BTreeMap<&'static str, TaskState>>> = LazyLock::new(Default::default);
+
+/// Background tasks that are currently down, split into critical and other tasks.
+#[derive(Debug, Default, PartialEq)]
+pub struct DownTasks {
+    pub critical: Vec<&'static str>,
+    pub other: Vec<&'static str>,
+}
+
+/// Lists the supervised tasks that are not running right now.
+pub fn down_tasks() -> DownTasks {
+    let tasks = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut down = DownTasks::default();
+    for (name, state) in tasks.iter().filter(|(_, state)| !state.alive) {
+        if state.critical {
+            down.critical.push(name);
+        } else {
+            down.other.push(name);
+        }
+    }
+    down
+}
+
+fn set_alive(name: &'static str, critical: bool, alive: bool) {
+    let mut tasks = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    tasks.insert(name, TaskState { critical, alive });
+    BACKGROUND_TASK_UP.with_label_values(&[name]).set(alive as i64);
+}
+
+/// Delay before the given restart: doubles from one second up to a minute.
+fn backoff(restarts: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(restarts))
+        .min(MAX_BACKOFF)
+}
+
+/// Runs a long-lived background task, tracking whether it is alive for `/ready` and `/metrics`.
+///
+/// `task` is called for every (re)start. A task that stops, whether it returned or panicked,
+/// is marked down and, with `options.restart`, started again after a backoff.
+pub fn spawn<F, Fut>(name: &'static str, options: TaskOptions, task: F)
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    set_alive(name, options.critical, true);
+
+    tokio::spawn(async move {
+        let mut restarts = 0;
+        loop {
+            match tokio::spawn(task()).await {
+                Ok(()) => tracing::error!(task = name, "Background task stopped"),
+                Err(e) => tracing::error!(task = name, error = %e, "Background task failed"),
+            }
+            set_alive(name, options.critical, false);
+
+            if !options.restart {
+                return;
+            }
+
+            let delay = backoff(restarts);
+            tracing::info!(task = name, delay_seconds = delay.as_secs(), "Restarting background task");
+            tokio::time::sleep(delay).await;
+
+            restarts += 1;
+            BACKGROUND_TASK_RESTARTS.with_label_values(&[name]).inc();
+            set_alive(name, options.critical, true);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(0), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(8));
+        assert_eq!(backoff(10), MAX_BACKOFF);
+        assert_eq!(backoff(u32::MAX), MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn test_stopped_task_is_reported_down() {
+        let options = TaskOptions {
+            critical: true,
+            restart: false,
+        };
+        spawn("test-crashing-task", options, || async { panic!("task crashed") });
+
+        for _ in 0..100 {
+            if down_tasks().critical.contains(&"test-crashing-task") {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("crashed task was not reported down");
+    }
+}
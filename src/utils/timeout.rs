@@ -0,0 +1,140 @@
+use crate::config;
+use crate::error::{AppError, Result};
+use futures::future::BoxFuture;
+use std::fmt;
+use std::future::Future;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tower::{BoxError, Layer, Service};
+
+tokio::task_local! {
+    static KUBE_TIMEOUT: Duration;
+}
+
+/// Runs `future` with `timeout` replacing `KUBE_TIMEOUT_SECONDS` for the Kubernetes API calls made within it.
+pub async fn scope<F: Future>(timeout: Duration, future: F) -> F::Output {
+    KUBE_TIMEOUT.scope(timeout, future).await
+}
+
+/// The Kubernetes API timeout for the current request: its override, or the configured default.
+pub fn current() -> Duration {
+    KUBE_TIMEOUT
+        .try_with(|timeout| *timeout)
+        .unwrap_or(config::get().kube_timeout)
+}
+
+/// Parses a `?timeout=` value (`90s` or `90`), which may not exceed `MAX_KUBE_TIMEOUT_SECONDS`.
+pub fn parse(value: &str) -> Result<Duration> {
+    let max = config::get().max_kube_timeout;
+    let seconds: u64 = value
+        .strip_suffix('s')
+        .unwrap_or(value)
+        .parse()
+        .ok()
+        .filter(|seconds| *seconds > 0)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!("Invalid timeout '{}': expected a number of seconds such as '60s'", value))
+        })?;
+
+    if seconds > max.as_secs() {
+        return Err(AppError::BadRequest(format!(
+            "Timeout {}s exceeds the maximum of {}s",
+            seconds,
+            max.as_secs()
+        )));
+    }
+
+    Ok(Duration::from_secs(seconds))
+}
+
+/// A Kubernetes API request that outlived the timeout of the request it was made for.
+#[derive(Debug)]
+pub struct TimedOut(pub Duration);
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Kubernetes API call did not complete within {}s", self.0.as_secs())
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Client middleware bounding each Kubernetes API request by the timeout of the request that made it.
+///
+/// The client is shared, so the timeout is read when the call is made rather than when the client is built.
+#[derive(Clone)]
+pub struct TimeoutLayer;
+
+impl<S> Layer<S> for TimeoutLayer {
+    type Service = TimeoutService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimeoutService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct TimeoutService<S> {
+    inner: S,
+}
+
+impl<S, Request> Service<Request> for TimeoutService<S>
+where
+    S: Service<Request>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, std::result::Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let timeout = current();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            match tokio::time::timeout(timeout, response).await {
+                Ok(result) => result.map_err(Into::into),
+                Err(_) => Err(TimedOut(timeout).into()),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse("90").unwrap(), Duration::from_secs(90));
+        assert!(matches!(parse("0s"), Err(AppError::BadRequest(_))));
+        assert!(matches!(parse("1m"), Err(AppError::BadRequest(_))));
+        assert!(matches!(parse("100000s"), Err(AppError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_layer_applies_request_timeout() {
+        let slow = tower::service_fn(|_: ()| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok::<_, BoxError>(())
+        });
+        let mut service = TimeoutLayer.layer(slow);
+
+        let error = scope(Duration::from_millis(10), async { service.call(()).await })
+            .await
+            .unwrap_err();
+        assert!(error.is::<TimedOut>());
+    }
+
+    #[tokio::test]
+    async fn test_scope_overrides_default() {
+        assert_eq!(current(), config::get().kube_timeout);
+        let inside = scope(Duration::from_secs(120), async { current() }).await;
+        assert_eq!(inside, Duration::from_secs(120));
+    }
+}
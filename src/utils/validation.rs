@@ -1,4 +1,49 @@
+use crate::config::ImageTagPolicy;
 use crate::error::{AppError, Result};
+use crate::models::cnpg::LdapRequest;
+use std::collections::HashMap;
+
+/// PostgreSQL parameters accepted in `postgresql_parameters`, sorted. Names containing a dot are
+/// extension settings (e.g. `pg_stat_statements.max`) and are not checked against this list.
+const KNOWN_POSTGRESQL_PARAMETERS: &[&str] = &[
+    "autovacuum", "autovacuum_analyze_scale_factor", "autovacuum_analyze_threshold",
+    "autovacuum_freeze_max_age", "autovacuum_max_workers", "autovacuum_multixact_freeze_max_age",
+    "autovacuum_naptime", "autovacuum_vacuum_cost_delay", "autovacuum_vacuum_cost_limit",
+    "autovacuum_vacuum_insert_scale_factor", "autovacuum_vacuum_insert_threshold",
+    "autovacuum_vacuum_scale_factor", "autovacuum_vacuum_threshold", "autovacuum_work_mem",
+    "checkpoint_completion_target", "checkpoint_flush_after", "checkpoint_timeout", "checkpoint_warning",
+    "client_min_messages", "commit_delay", "commit_siblings", "cpu_index_tuple_cost", "cpu_operator_cost",
+    "cpu_tuple_cost", "cursor_tuple_fraction", "datestyle", "deadlock_timeout", "default_statistics_target",
+    "default_text_search_config", "default_transaction_isolation", "default_transaction_read_only",
+    "effective_cache_size", "effective_io_concurrency", "enable_bitmapscan", "enable_hashagg",
+    "enable_hashjoin", "enable_indexonlyscan", "enable_indexscan", "enable_material", "enable_mergejoin",
+    "enable_nestloop", "enable_parallel_append", "enable_parallel_hash", "enable_partition_pruning",
+    "enable_partitionwise_aggregate", "enable_partitionwise_join", "enable_seqscan", "enable_sort",
+    "enable_tidscan", "extra_float_digits", "from_collapse_limit", "geqo", "geqo_threshold",
+    "hot_standby_feedback", "huge_pages", "idle_in_transaction_session_timeout", "idle_session_timeout",
+    "intervalstyle", "jit", "join_collapse_limit", "lc_messages", "lc_monetary", "lc_numeric", "lc_time",
+    "lock_timeout", "log_autovacuum_min_duration", "log_checkpoints", "log_connections", "log_disconnections",
+    "log_duration", "log_error_verbosity", "log_line_prefix", "log_lock_waits", "log_min_duration_sample",
+    "log_min_duration_statement", "log_min_error_statement", "log_min_messages", "log_parameter_max_length",
+    "log_parameter_max_length_on_error", "log_replication_commands", "log_statement",
+    "log_statement_sample_rate", "log_temp_files", "log_timezone", "logical_decoding_work_mem",
+    "maintenance_io_concurrency", "maintenance_work_mem", "max_connections", "max_files_per_process",
+    "max_locks_per_transaction", "max_logical_replication_workers", "max_parallel_maintenance_workers",
+    "max_parallel_workers", "max_parallel_workers_per_gather", "max_pred_locks_per_transaction",
+    "max_prepared_transactions", "max_replication_slots", "max_slot_wal_keep_size", "max_stack_depth",
+    "max_standby_archive_delay", "max_standby_streaming_delay", "max_sync_workers_per_subscription",
+    "max_wal_senders", "max_wal_size", "max_worker_processes", "min_parallel_index_scan_size",
+    "min_parallel_table_scan_size", "min_wal_size", "parallel_leader_participation", "parallel_setup_cost",
+    "parallel_tuple_cost", "password_encryption", "random_page_cost", "search_path", "seq_page_cost",
+    "shared_buffers", "shared_preload_libraries", "statement_timeout", "synchronous_commit",
+    "synchronous_standby_names", "tcp_keepalives_count", "tcp_keepalives_idle", "tcp_keepalives_interval",
+    "temp_buffers", "temp_file_limit", "timezone", "track_activities", "track_activity_query_size",
+    "track_commit_timestamp", "track_counts", "track_functions", "track_io_timing", "track_wal_io_timing",
+    "vacuum_cost_delay", "vacuum_cost_limit", "vacuum_freeze_min_age", "vacuum_freeze_table_age",
+    "vacuum_multixact_freeze_min_age", "vacuum_multixact_freeze_table_age", "wal_buffers", "wal_compression",
+    "wal_keep_size", "wal_receiver_timeout", "wal_sender_timeout", "wal_writer_delay", "wal_writer_flush_after",
+    "work_mem",
+];
 
 /// Validates a Kubernetes resource name
 pub fn validate_resource_name(name: &str) -> Result<()> {
@@ -47,9 +92,9 @@ pub fn validate_cpu_resource(cpu: &str) -> Result<()> {
     }
     
     // Simple validation for CPU format (e.g., "100m", "1", "2.5")
-    let is_valid = if cpu.ends_with('m') {
+    let is_valid = if let Some(millicores) = cpu.strip_suffix('m') {
         // Millicores format
-        cpu[..cpu.len()-1].parse::<u32>().is_ok()
+        millicores.parse::<u32>().is_ok()
     } else {
         // Cores format
         cpu.parse::<f64>().is_ok()
@@ -99,8 +144,19 @@ pub fn validate_storage_size(size: &str) -> Result<()> {
     validate_memory_resource(size) // Same validation as memory
 }
 
-/// Validates container image name
-pub fn validate_image_name(image: &str) -> Result<()> {
+/// Rejects a request body whose namespace disagrees with the namespace in the path
+pub fn validate_namespace_matches_path(path_namespace: &str, body_namespace: Option<&str>) -> Result<()> {
+    match body_namespace {
+        Some(body_namespace) if body_namespace != path_namespace => Err(AppError::BadRequest(format!(
+            "Body namespace '{}' does not match path namespace '{}'",
+            body_namespace, path_namespace
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Validates container image name, and its tag or digest against `policy`
+pub fn validate_image_name(image: &str, policy: ImageTagPolicy) -> Result<()> {
     if image.is_empty() {
         return Err(AppError::Validation("Container image cannot be empty".to_string()));
     }
@@ -112,9 +168,273 @@ pub fn validate_image_name(image: &str) -> Result<()> {
         ));
     }
     
+    let (reference, digest) = match image.split_once('@') {
+        Some((reference, digest)) => (reference, Some(digest)),
+        None => (image, None),
+    };
+    // A ':' before the last '/' belongs to a registry port, not a tag
+    let tag = reference
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.split_once(':'))
+        .map(|(_, tag)| tag);
+    
+    match policy {
+        ImageTagPolicy::Any => Ok(()),
+        ImageTagPolicy::ForbidLatest if digest.is_none() && tag.is_none_or(|tag| tag == "latest") => {
+            Err(AppError::Validation(format!(
+                "Image '{}' uses a mutable tag; pin a version (e.g. '{}:1.2.3') or a digest ('@sha256:...')",
+                image, reference.trim_end_matches(":latest")
+            )))
+        }
+        ImageTagPolicy::RequireDigest if digest.is_none() => Err(AppError::Validation(format!(
+            "Image '{}' must be pinned by digest, e.g. '{}@sha256:...'",
+            image, reference
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Validates environment variable names as Kubernetes accepts them
+pub fn validate_env_var_names(env: &HashMap<String, String>) -> Result<()> {
+    for name in env.keys() {
+        let mut chars = name.chars();
+        let valid = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || matches!(c, '_' | '-' | '.'))
+            && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        
+        if !valid {
+            return Err(AppError::Validation(format!(
+                "Invalid environment variable name '{}': must consist of letters, digits, '_', '-' or '.', and must not start with a digit",
+                name
+            )));
+        }
+    }
+    
+    Ok(())
+}
+
+/// Validates the operator and effect of a pod toleration
+pub fn validate_toleration(key: Option<&str>, operator: Option<&str>, effect: Option<&str>) -> Result<()> {
+    if !matches!(operator, None | Some("Exists") | Some("Equal")) {
+        return Err(AppError::Validation(
+            "Toleration operator must be 'Exists' or 'Equal'".to_string()
+        ));
+    }
+    
+    if key.unwrap_or("").is_empty() && operator != Some("Exists") {
+        return Err(AppError::Validation(
+            "A toleration without a key must use the 'Exists' operator".to_string()
+        ));
+    }
+    
+    if !matches!(effect, None | Some("") | Some("NoSchedule") | Some("PreferNoSchedule") | Some("NoExecute")) {
+        return Err(AppError::Validation(
+            "Toleration effect must be 'NoSchedule', 'PreferNoSchedule' or 'NoExecute'".to_string()
+        ));
+    }
+    
+    Ok(())
+}
+
+/// Validates the names of container resource claims: DNS labels, each used once
+pub fn validate_resource_claim_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<()> {
+    let mut seen = std::collections::HashSet::new();
+    
+    for name in names {
+        let is_valid = !name.is_empty()
+            && name.len() <= 63
+            && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            && !name.starts_with('-')
+            && !name.ends_with('-');
+        
+        if !is_valid {
+            return Err(AppError::Validation(format!(
+                "Invalid resource claim name '{}': must be at most 63 lowercase alphanumeric characters or '-', and cannot start or end with '-'",
+                name
+            )));
+        }
+        
+        if !seen.insert(name) {
+            return Err(AppError::Validation(format!("Duplicate resource claim name '{}'", name)));
+        }
+    }
+    
+    Ok(())
+}
+
+/// Validates a pod topology spread constraint
+pub fn validate_topology_spread_constraint(max_skew: i32, topology_key: &str, when_unsatisfiable: Option<&str>) -> Result<()> {
+    if max_skew < 1 {
+        return Err(AppError::Validation("Topology spread maxSkew must be at least 1".to_string()));
+    }
+    
+    validate_label_key(topology_key)
+        .map_err(|_| AppError::Validation(format!("Invalid topology key '{}': must be a valid node label key", topology_key)))?;
+    
+    if !matches!(when_unsatisfiable, None | Some("DoNotSchedule") | Some("ScheduleAnyway")) {
+        return Err(AppError::Validation(
+            "Topology spread whenUnsatisfiable must be 'DoNotSchedule' or 'ScheduleAnyway'".to_string()
+        ));
+    }
+    
+    Ok(())
+}
+
+/// Validates Kubernetes label keys and values
+pub fn validate_labels(labels: &HashMap<String, String>) -> Result<()> {
+    for (key, value) in labels {
+        validate_label_key(key)?;
+        validate_label_value(key, value)?;
+    }
+    
+    Ok(())
+}
+
+/// Validates annotation keys, which follow the label key syntax, and the total size the API server allows
+pub fn validate_annotations(annotations: &HashMap<String, String>) -> Result<()> {
+    const MAX_TOTAL_SIZE: usize = 256 * 1024;
+    
+    for key in annotations.keys() {
+        validate_label_key(key).map_err(|_| {
+            AppError::Validation(format!(
+                "Invalid annotation key '{}': must be an optional DNS subdomain prefix and '/', followed by 1-63 alphanumeric characters, '-', '_' or '.'",
+                key
+            ))
+        })?;
+    }
+    
+    let total_size: usize = annotations.iter().map(|(key, value)| key.len() + value.len()).sum();
+    if total_size > MAX_TOTAL_SIZE {
+        return Err(AppError::Validation(format!(
+            "Annotations total {} bytes; at most {} are allowed",
+            total_size, MAX_TOTAL_SIZE
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Validates a label selector in Kubernetes syntax (`key`, `!key`, `key=value`, `key!=value`,
+/// `key in (a,b)`, `key notin (a,b)`, comma-separated)
+pub fn validate_label_selector(selector: &str) -> Result<()> {
+    let invalid = |reason: String| AppError::BadRequest(format!("Invalid label selector '{}': {}", selector, reason));
+    
+    for requirement in split_requirements(selector) {
+        let requirement = requirement.trim();
+        if requirement.is_empty() {
+            return Err(invalid("empty requirement".to_string()));
+        }
+        
+        let (key, values): (&str, Vec<&str>) = if let Some((head, set)) = requirement.split_once('(') {
+            let Some(set) = set.strip_suffix(')') else {
+                return Err(invalid(format!("unclosed value set in '{}'", requirement)));
+            };
+            let (key, operator) = head.trim().rsplit_once(char::is_whitespace).unwrap_or((head, ""));
+            if !matches!(operator, "in" | "notin") {
+                return Err(invalid(format!("expected 'in' or 'notin' in '{}'", requirement)));
+            }
+            (key.trim(), set.split(',').map(str::trim).collect())
+        } else if let Some(key) = requirement.strip_prefix('!') {
+            (key.trim(), Vec::new())
+        } else if let Some((key, value)) = requirement
+            .split_once("!=")
+            .or_else(|| requirement.split_once("=="))
+            .or_else(|| requirement.split_once('='))
+        {
+            (key.trim(), vec![value.trim()])
+        } else {
+            (requirement, Vec::new())
+        };
+        
+        validate_label_key(key).map_err(|e| invalid(e.to_string()))?;
+        for value in values {
+            validate_label_value(key, value).map_err(|e| invalid(e.to_string()))?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Splits a selector at the commas that separate requirements, not those inside value sets.
+fn split_requirements(selector: &str) -> Vec<&str> {
+    let mut requirements = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (index, c) in selector.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                requirements.push(&selector[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    requirements.push(&selector[start..]);
+    requirements
+}
+
+fn validate_label_key(key: &str) -> Result<()> {
+    let (prefix, name) = match key.split_once('/') {
+        Some((prefix, name)) => (Some(prefix), name),
+        None => (None, key),
+    };
+    
+    if let Some(prefix) = prefix {
+        let is_valid_prefix = !prefix.is_empty()
+            && prefix.len() <= 253
+            && prefix.split('.').all(|part| {
+                !part.is_empty()
+                    && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+                    && !part.starts_with('-')
+                    && !part.ends_with('-')
+            });
+        
+        if !is_valid_prefix {
+            return Err(AppError::Validation(format!(
+                "Invalid label key '{}': prefix must be a DNS subdomain", key
+            )));
+        }
+    }
+    
+    if name.is_empty() || !is_label_segment(name) {
+        return Err(AppError::Validation(format!(
+            "Invalid label key '{}': name must be 1-63 alphanumeric characters, '-', '_' or '.', starting and ending with an alphanumeric character", key
+        )));
+    }
+    
     Ok(())
 }
 
+fn validate_label_value(key: &str, value: &str) -> Result<()> {
+    if !value.is_empty() && !is_label_segment(value) {
+        return Err(AppError::Validation(format!(
+            "Invalid value for label '{}': must be at most 63 alphanumeric characters, '-', '_' or '.', starting and ending with an alphanumeric character", key
+        )));
+    }
+    
+    Ok(())
+}
+
+fn is_label_segment(segment: &str) -> bool {
+    segment.len() <= 63
+        && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        && segment.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && segment.ends_with(|c: char| c.is_ascii_alphanumeric())
+}
+
+/// Validates a container image pull policy
+pub fn validate_image_pull_policy(policy: &str) -> Result<()> {
+    match policy {
+        "Always" | "IfNotPresent" | "Never" => Ok(()),
+        _ => Err(AppError::Validation(
+            "Invalid image pull policy. Use 'Always', 'IfNotPresent', or 'Never'".to_string()
+        )),
+    }
+}
+
 /// Validates PostgreSQL database name
 pub fn validate_database_name(db_name: &str) -> Result<()> {
     if db_name.is_empty() {
@@ -143,6 +463,369 @@ pub fn validate_database_name(db_name: &str) -> Result<()> {
     Ok(())
 }
 
+/// Roles created and managed by PostgreSQL or the CNPG operator
+const RESERVED_POSTGRES_ROLES: &[&str] = &["postgres", "streaming_replica", "cnpg_pooler_pgbouncer"];
+
+/// Rejects database owners that collide with reserved roles (including PostgreSQL's `pg_` prefix)
+pub fn validate_database_owner(owner: &str, allow_reserved: bool) -> Result<()> {
+    validate_database_name(owner)?;
+    
+    let reserved = RESERVED_POSTGRES_ROLES.iter().any(|role| role.eq_ignore_ascii_case(owner))
+        || owner.to_ascii_lowercase().starts_with("pg_");
+    if reserved && !allow_reserved {
+        return Err(AppError::Validation(format!(
+            "Database owner '{}' is a reserved role managed by PostgreSQL or the CNPG operator",
+            owner
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Rejects PostgreSQL parameters on the denylist (compared case-insensitively, as PostgreSQL does) and,
+/// unless `allow_unknown`, parameters that are not known settings, suggesting the closest known name
+pub fn validate_postgresql_parameters(
+    parameters: &HashMap<String, String>,
+    denylist: &[String],
+    allow_unknown: bool,
+) -> Result<()> {
+    let mut keys: Vec<&String> = parameters.keys().collect();
+    keys.sort();
+    
+    for key in keys {
+        if denylist.iter().any(|denied| denied.eq_ignore_ascii_case(key)) {
+            return Err(AppError::Validation(format!(
+                "PostgreSQL parameter '{}' is managed by the operator and cannot be set",
+                key
+            )));
+        }
+        
+        let lowercase = key.to_ascii_lowercase();
+        if allow_unknown || lowercase.contains('.') || KNOWN_POSTGRESQL_PARAMETERS.binary_search(&lowercase.as_str()).is_ok() {
+            continue;
+        }
+        
+        let suggestion = KNOWN_POSTGRESQL_PARAMETERS
+            .iter()
+            .map(|known| (edit_distance(&lowercase, known), *known))
+            .min()
+            .filter(|(distance, _)| *distance <= 3)
+            .map(|(_, known)| format!("; did you mean '{}'?", known))
+            .unwrap_or_default();
+        return Err(AppError::Validation(format!(
+            "Unknown PostgreSQL parameter '{}'{}",
+            key, suggestion
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Levenshtein distance between two ASCII strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    
+    for (i, &ca) in a.as_bytes().iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    
+    previous[b.len()]
+}
+
+/// Validates a CNPG `ScheduledBackup` schedule.
+///
+/// CNPG uses six cron fields with seconds first (`0 0 2 * * *`), or a descriptor such as `@daily`.
+pub fn validate_cron_schedule(schedule: &str) -> Result<()> {
+    const DESCRIPTORS: &[&str] = &["@yearly", "@annually", "@monthly", "@weekly", "@daily", "@midnight", "@hourly"];
+    const MONTHS: &[&str] = &["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+    const WEEKDAYS: &[&str] = &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+    let schedule = schedule.trim();
+    if DESCRIPTORS.contains(&schedule) {
+        return Ok(());
+    }
+    if let Some(interval) = schedule.strip_prefix("@every ") {
+        if !is_go_duration(interval.trim()) {
+            return Err(AppError::Validation(format!(
+                "Invalid interval in schedule '{}'; use a duration such as '6h' or '1h30m'",
+                schedule
+            )));
+        }
+        return Ok(());
+    }
+
+    let fields: Vec<&str> = schedule.split_whitespace().collect();
+    if fields.len() != 6 {
+        let hint = if fields.len() == 5 {
+            "; CNPG schedules start with a seconds field, e.g. '0 0 2 * * *'"
+        } else {
+            ""
+        };
+        return Err(AppError::Validation(format!(
+            "Schedule '{}' must have six fields (second minute hour day-of-month month day-of-week){}",
+            schedule, hint
+        )));
+    }
+
+    let specs: [(&str, u32, u32, &[&str], u32); 6] = [
+        ("second", 0, 59, &[], 0),
+        ("minute", 0, 59, &[], 0),
+        ("hour", 0, 23, &[], 0),
+        ("day-of-month", 1, 31, &[], 0),
+        ("month", 1, 12, MONTHS, 1),
+        ("day-of-week", 0, 6, WEEKDAYS, 0),
+    ];
+    for (field, (label, min, max, names, offset)) in fields.iter().zip(specs) {
+        let value = |part: &str| -> Option<u32> {
+            if let Some(index) = names.iter().position(|n| n.eq_ignore_ascii_case(part)) {
+                return Some(index as u32 + offset);
+            }
+            part.parse::<u32>().ok().filter(|v| (min..=max).contains(v))
+        };
+        let valid = field.split(',').all(|item| {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, Some(step)),
+                None => (item, None),
+            };
+            if step.is_some_and(|step| step.parse::<u32>().map_or(true, |step| step == 0)) {
+                return false;
+            }
+            match range {
+                "*" | "?" => true,
+                _ => match range.split_once('-') {
+                    Some((start, end)) => matches!((value(start), value(end)), (Some(start), Some(end)) if start <= end),
+                    None => value(range).is_some(),
+                },
+            }
+        });
+        if !valid {
+            return Err(AppError::Validation(format!(
+                "Invalid {} field '{}' in schedule '{}'; expected values {}-{}",
+                label, field, schedule, min, max
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `value` is a Go duration such as `90s` or `1h30m`, as accepted by `@every`.
+fn is_go_duration(value: &str) -> bool {
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+        if digits == 0 || rest[..digits].parse::<f64>().is_err() {
+            return false;
+        }
+        rest = &rest[digits..];
+        let unit = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        if !matches!(&rest[..unit], "h" | "m" | "s" | "ms" | "us" | "µs" | "ns") {
+            return false;
+        }
+        rest = &rest[unit..];
+    }
+    !value.is_empty()
+}
+
+/// Validates the optional `method` and `target` of a CNPG backup
+pub fn validate_backup_options(method: Option<&str>, target: Option<&str>) -> Result<()> {
+    if let Some(method) = method
+        && !matches!(method, "barmanObjectStore" | "volumeSnapshot" | "plugin")
+    {
+        return Err(AppError::Validation(format!(
+            "Invalid backup method '{}'; use 'barmanObjectStore', 'volumeSnapshot' or 'plugin'",
+            method
+        )));
+    }
+    
+    if let Some(target) = target
+        && !matches!(target, "primary" | "prefer-standby")
+    {
+        return Err(AppError::Validation(format!(
+            "Invalid backup target '{}'; use 'primary' or 'prefer-standby'",
+            target
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Validates a requested storage class against the allow-list; any class is accepted without one
+pub fn validate_storage_class(storage_class: &str, allowed: Option<&[String]>) -> Result<()> {
+    validate_resource_name(storage_class)?;
+    
+    if let Some(allowed) = allowed
+        && !allowed.iter().any(|class| class == storage_class)
+    {
+        return Err(AppError::Validation(format!(
+            "Storage class '{}' is not allowed; use one of: {}",
+            storage_class,
+            allowed.join(", ")
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Validates a GPU count: a whole number, at most `max` when a per-notebook cap is configured
+pub fn validate_gpu_count(gpu: &str, max: Option<u32>) -> Result<()> {
+    let count: u32 = gpu.parse().map_err(|_| {
+        AppError::Validation(format!("Invalid GPU count '{}'. GPUs must be a whole number like '1'", gpu))
+    })?;
+    
+    if let Some(max) = max
+        && count > max
+    {
+        return Err(AppError::Validation(format!(
+            "GPU count {} exceeds the maximum of {} per notebook",
+            count, max
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Validates a container command: no empty entries
+pub fn validate_container_command(command: &[String]) -> Result<()> {
+    if command.iter().any(|entry| entry.trim().is_empty()) {
+        return Err(AppError::Validation("command entries cannot be empty".to_string()));
+    }
+    
+    Ok(())
+}
+
+/// Validates `pg_ident.conf` map entries: each a single, non-empty line
+pub fn validate_pg_ident(entries: &[String]) -> Result<()> {
+    for entry in entries {
+        if entry.trim().is_empty() {
+            return Err(AppError::Validation("pg_ident entries cannot be empty".to_string()));
+        }
+        
+        if entry.contains(['\n', '\r']) {
+            return Err(AppError::Validation(format!(
+                "pg_ident entry '{}' must be a single line",
+                entry.escape_debug()
+            )));
+        }
+    }
+    
+    Ok(())
+}
+
+/// Validates LDAP settings: exactly one of search+bind (`base_dn`) or simple bind
+/// (`bind_prefix`/`bind_suffix`), with the fields of the chosen mode given together
+pub fn validate_ldap(ldap: &LdapRequest) -> Result<()> {
+    let invalid = |message: &str| Err(AppError::Validation(format!("Invalid LDAP configuration: {}", message)));
+    
+    if ldap.server.trim().is_empty() || ldap.server.contains(char::is_whitespace) {
+        return invalid("server must be a host name without whitespace");
+    }
+    
+    if let Some(port) = ldap.port
+        && !(1..=65535).contains(&port)
+    {
+        return invalid("port must be between 1 and 65535");
+    }
+    
+    if let Some(ref scheme) = ldap.scheme
+        && scheme != "ldap"
+        && scheme != "ldaps"
+    {
+        return invalid("scheme must be 'ldap' or 'ldaps'");
+    }
+    
+    let search_bind = ldap.base_dn.is_some();
+    let simple_bind = ldap.bind_prefix.is_some() || ldap.bind_suffix.is_some();
+    match (search_bind, simple_bind) {
+        (true, true) => return invalid("base_dn (search+bind) cannot be combined with bind_prefix/bind_suffix (simple bind)"),
+        (false, false) => return invalid("set base_dn for search+bind, or bind_prefix/bind_suffix for simple bind"),
+        _ => {}
+    }
+    
+    let search_fields = ldap.bind_dn.is_some()
+        || ldap.bind_password_secret.is_some()
+        || ldap.search_attribute.is_some()
+        || ldap.search_filter.is_some();
+    if simple_bind && search_fields {
+        return invalid("bind_dn, bind_password_secret, search_attribute and search_filter require base_dn");
+    }
+    
+    if ldap.bind_dn.is_some() != ldap.bind_password_secret.is_some() {
+        return invalid("bind_dn and bind_password_secret must be set together");
+    }
+    
+    if ldap.bind_password_key.is_some() && ldap.bind_password_secret.is_none() {
+        return invalid("bind_password_key requires bind_password_secret");
+    }
+    
+    if ldap.search_attribute.is_some() && ldap.search_filter.is_some() {
+        return invalid("search_attribute and search_filter cannot be combined");
+    }
+    
+    if let Some(ref secret) = ldap.bind_password_secret {
+        validate_resource_name(secret)?;
+    }
+    
+    Ok(())
+}
+
+/// Checks PostgreSQL parameters that depend on the number of instances, returning one message per conflict
+pub fn instance_parameter_conflicts(instances: i32, parameters: &HashMap<String, String>) -> Vec<String> {
+    let replicas = (instances - 1).max(0);
+    let mut conflicts = Vec::new();
+    
+    // Each replica holds a WAL sender and, with CNPG's HA slots, a replication slot on the primary
+    for key in ["max_wal_senders", "max_replication_slots"] {
+        if let Some(value) = parameters.get(key)
+            && let Ok(value) = value.trim().parse::<i32>()
+            && value < replicas
+        {
+            conflicts.push(format!(
+                "{} = {} is less than the {} replicas of a {}-instance cluster",
+                key, value, replicas, instances
+            ));
+        }
+    }
+    
+    if let Some(names) = parameters.get("synchronous_standby_names")
+        && !names.trim().is_empty()
+    {
+        let required = synchronous_standby_count(names);
+        if required > replicas {
+            conflicts.push(format!(
+                "synchronous_standby_names waits for {} standby(s) but a {}-instance cluster has {} replicas; commits would block",
+                required, instances, replicas
+            ));
+        }
+    }
+    
+    conflicts
+}
+
+/// Number of standbys a `synchronous_standby_names` value waits for: `FIRST n (...)`, `ANY n (...)`,
+/// `n (...)`, or 1 for a plain list
+fn synchronous_standby_count(value: &str) -> i32 {
+    let value = value.trim();
+    let upper = value.to_ascii_uppercase();
+    let rest = upper
+        .strip_prefix("FIRST")
+        .or_else(|| upper.strip_prefix("ANY"))
+        .unwrap_or(&upper)
+        .trim_start();
+    
+    let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+    match digits.parse() {
+        Ok(count) if rest[digits.len()..].trim_start().starts_with('(') => count,
+        _ => 1,
+    }
+}
+
 /// Validates PostgreSQL instance count
 pub fn validate_instance_count(instances: i32) -> Result<()> {
     if instances < 1 {
@@ -158,6 +841,49 @@ pub fn validate_instance_count(instances: i32) -> Result<()> {
     Ok(())
 }
 
+/// Validates synchronous replication against the instance count: at least one synchronous standby,
+/// fewer than the instances, and set whenever quorum-based failover is enabled
+pub fn validate_synchronous_replicas(instances: i32, synchronous_replicas: Option<i32>, failover_quorum: Option<bool>) -> Result<()> {
+    let Some(number) = synchronous_replicas else {
+        if failover_quorum == Some(true) {
+            return Err(AppError::Validation(
+                "failover_quorum requires synchronous_replicas".to_string()
+            ));
+        }
+        return Ok(());
+    };
+    
+    if number < 1 {
+        return Err(AppError::Validation("synchronous_replicas must be at least 1".to_string()));
+    }
+    
+    if number >= instances {
+        return Err(AppError::Validation(format!(
+            "synchronous_replicas ({}) must be less than instances ({})",
+            number, instances
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Validates a duration in seconds as accepted by CNPG's int32 delay/timeout fields
+pub fn validate_duration_seconds(field: &str, seconds: i64) -> Result<()> {
+    if seconds < 0 {
+        return Err(AppError::Validation(format!("{} must not be negative", field)));
+    }
+    
+    if seconds > i32::MAX as i64 {
+        return Err(AppError::Validation(format!(
+            "{} cannot exceed {} seconds",
+            field,
+            i32::MAX
+        )));
+    }
+    
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -190,4 +916,279 @@ mod tests {
         assert!(validate_memory_resource("1GB").is_err());
         assert!(validate_memory_resource("invalid").is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_validate_image_tag_policy() {
+        let pinned = "nginx@sha256:0d17b565c37bcbd895e9d92315a05c1c3c9a29f762b011a10c54a66cd53c9b31";
+        
+        for image in ["nginx", "nginx:latest", "nginx:1.25", pinned] {
+            assert!(validate_image_name(image, ImageTagPolicy::Any).is_ok());
+        }
+        
+        assert!(validate_image_name("nginx", ImageTagPolicy::ForbidLatest).is_err());
+        assert!(validate_image_name("nginx:latest", ImageTagPolicy::ForbidLatest).is_err());
+        assert!(validate_image_name("nginx:1.25", ImageTagPolicy::ForbidLatest).is_ok());
+        assert!(validate_image_name("registry.local:5000/nginx", ImageTagPolicy::ForbidLatest).is_err());
+        assert!(validate_image_name(pinned, ImageTagPolicy::ForbidLatest).is_ok());
+        
+        assert!(validate_image_name("nginx", ImageTagPolicy::RequireDigest).is_err());
+        assert!(validate_image_name("nginx:1.25", ImageTagPolicy::RequireDigest).is_err());
+        assert!(validate_image_name(pinned, ImageTagPolicy::RequireDigest).is_ok());
+    }
+    
+    #[test]
+    fn test_validate_labels() {
+        let valid = HashMap::from([
+            ("team".to_string(), "data-science".to_string()),
+            ("app.kubernetes.io/name".to_string(), "notebook".to_string()),
+            ("cost-center".to_string(), "".to_string()),
+        ]);
+        assert!(validate_labels(&valid).is_ok());
+        
+        let bad_key = HashMap::from([("-team".to_string(), "a".to_string())]);
+        assert!(validate_labels(&bad_key).is_err());
+        
+        let bad_prefix = HashMap::from([("Example.com/team".to_string(), "a".to_string())]);
+        assert!(validate_labels(&bad_prefix).is_err());
+        
+        let bad_value = HashMap::from([("team".to_string(), "has spaces".to_string())]);
+        assert!(validate_labels(&bad_value).is_err());
+    }
+
+    #[test]
+    fn test_validate_annotations() {
+        let valid = HashMap::from([
+            ("example.com/owner".to_string(), "Data Platform <data@example.com>".to_string()),
+            ("description".to_string(), "".to_string()),
+        ]);
+        assert!(validate_annotations(&valid).is_ok());
+        
+        let bad_key = HashMap::from([("owner/".to_string(), "a".to_string())]);
+        assert!(validate_annotations(&bad_key).is_err());
+        
+        let too_large = HashMap::from([("description".to_string(), "x".repeat(256 * 1024))]);
+        assert!(validate_annotations(&too_large).is_err());
+    }
+
+    #[test]
+    fn test_validate_resource_claim_names() {
+        assert!(validate_resource_claim_names(["gpu", "fpga-0"]).is_ok());
+        assert!(validate_resource_claim_names([""]).is_err());
+        assert!(validate_resource_claim_names(["GPU"]).is_err());
+        assert!(validate_resource_claim_names(["gpu", "gpu"]).is_err());
+        assert!(validate_resource_claim_names(["a".repeat(64).as_str()]).is_err());
+    }
+
+    #[test]
+    fn test_validate_topology_spread_constraint() {
+        assert!(validate_topology_spread_constraint(1, "topology.kubernetes.io/zone", None).is_ok());
+        assert!(validate_topology_spread_constraint(2, "kubernetes.io/hostname", Some("ScheduleAnyway")).is_ok());
+        assert!(validate_topology_spread_constraint(0, "kubernetes.io/hostname", None).is_err());
+        assert!(validate_topology_spread_constraint(1, "", None).is_err());
+        assert!(validate_topology_spread_constraint(1, "kubernetes.io/hostname", Some("Never")).is_err());
+    }
+
+    #[test]
+    fn test_validate_duration_seconds() {
+        assert!(validate_duration_seconds("switchover_delay", 0).is_ok());
+        assert!(validate_duration_seconds("switchover_delay", 3600).is_ok());
+        assert!(validate_duration_seconds("switchover_delay", -1).is_err());
+        assert!(validate_duration_seconds("switchover_delay", i64::from(i32::MAX) + 1).is_err());
+    }
+
+    #[test]
+    fn test_validate_env_var_names() {
+        let env = |name: &str| HashMap::from([(name.to_string(), "value".to_string())]);
+        assert!(validate_env_var_names(&env("PGAUDIT_LOG")).is_ok());
+        assert!(validate_env_var_names(&env("my.setting-1")).is_ok());
+        assert!(validate_env_var_names(&env("")).is_err());
+        assert!(validate_env_var_names(&env("1ABC")).is_err());
+        assert!(validate_env_var_names(&env("HAS SPACE")).is_err());
+    }
+
+    #[test]
+    fn test_validate_namespace_matches_path() {
+        assert!(validate_namespace_matches_path("team-a", None).is_ok());
+        assert!(validate_namespace_matches_path("team-a", Some("team-a")).is_ok());
+        assert!(matches!(
+            validate_namespace_matches_path("team-a", Some("team-b")),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_database_owner() {
+        assert!(validate_database_owner("app", false).is_ok());
+        assert!(validate_database_owner("postgres", false).is_err());
+        assert!(validate_database_owner("Streaming_Replica", false).is_err());
+        assert!(validate_database_owner("pg_monitor", false).is_err());
+        assert!(validate_database_owner("postgres", true).is_ok());
+        assert!(validate_database_owner("1app", true).is_err());
+    }
+
+    #[test]
+    fn test_validate_storage_class() {
+        let allowed = vec!["standard".to_string(), "fast-ssd".to_string()];
+        
+        assert!(validate_storage_class("premium", None).is_ok());
+        assert!(validate_storage_class("fast-ssd", Some(&allowed)).is_ok());
+        assert!(validate_storage_class("premium", Some(&allowed)).is_err());
+        assert!(validate_storage_class("Fast_SSD", None).is_err());
+    }
+    
+    #[test]
+    fn test_validate_label_selector() {
+        assert!(validate_label_selector("app=db").is_ok());
+        assert!(validate_label_selector("app==db,tier!=cache,!legacy,team").is_ok());
+        assert!(validate_label_selector("tier in (primary, replica),env notin (dev)").is_ok());
+        assert!(validate_label_selector("example.com/owner=team-a").is_ok());
+        
+        assert!(matches!(validate_label_selector("app=db,"), Err(AppError::BadRequest(_))));
+        assert!(matches!(validate_label_selector("app=-db"), Err(AppError::BadRequest(_))));
+        assert!(matches!(validate_label_selector("tier in (primary"), Err(AppError::BadRequest(_))));
+        assert!(matches!(validate_label_selector("tier within (primary)"), Err(AppError::BadRequest(_))));
+    }
+    
+    #[test]
+    fn test_validate_synchronous_replicas() {
+        assert!(validate_synchronous_replicas(1, None, None).is_ok());
+        assert!(validate_synchronous_replicas(3, Some(2), Some(true)).is_ok());
+        assert!(validate_synchronous_replicas(3, Some(3), None).is_err());
+        assert!(validate_synchronous_replicas(3, Some(0), None).is_err());
+        assert!(validate_synchronous_replicas(3, None, Some(true)).is_err());
+        assert!(validate_synchronous_replicas(3, None, Some(false)).is_ok());
+    }
+    
+    #[test]
+    fn test_validate_gpu_count() {
+        assert!(validate_gpu_count("8", None).is_ok());
+        assert!(validate_gpu_count("2", Some(2)).is_ok());
+        assert!(validate_gpu_count("3", Some(2)).is_err());
+        assert!(validate_gpu_count("0.5", None).is_err());
+        assert!(validate_gpu_count("", None).is_err());
+    }
+    
+    #[test]
+    fn test_validate_container_command() {
+        assert!(validate_container_command(&["start-notebook.sh".to_string()]).is_ok());
+        assert!(validate_container_command(&[]).is_ok());
+        assert!(validate_container_command(&["jupyter".to_string(), " ".to_string()]).is_err());
+    }
+    
+    #[test]
+    fn test_validate_pg_ident() {
+        assert!(validate_pg_ident(&["certmap /^(.*)@example\\.com$ \\1".to_string()]).is_ok());
+        assert!(validate_pg_ident(&[]).is_ok());
+        assert!(validate_pg_ident(&["  ".to_string()]).is_err());
+        assert!(validate_pg_ident(&["a b c\nd e f".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_validate_postgresql_parameters() {
+        let denylist = vec!["wal_level".to_string()];
+        let params = |key: &str| HashMap::from([(key.to_string(), "x".to_string())]);
+        
+        assert!(validate_postgresql_parameters(&params("work_mem"), &denylist, false).is_ok());
+        assert!(validate_postgresql_parameters(&params("wal_level"), &denylist, false).is_err());
+        assert!(validate_postgresql_parameters(&params("WAL_Level"), &denylist, true).is_err());
+        
+        assert!(validate_postgresql_parameters(&params("pg_stat_statements.max"), &denylist, false).is_ok());
+        assert!(validate_postgresql_parameters(&params("max_connetions"), &denylist, true).is_ok());
+        match validate_postgresql_parameters(&params("max_connetions"), &denylist, false) {
+            Err(AppError::Validation(message)) => assert!(message.contains("did you mean 'max_connections'?")),
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_known_postgresql_parameters_are_sorted() {
+        assert!(KNOWN_POSTGRESQL_PARAMETERS.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    fn ldap(value: serde_json::Value) -> LdapRequest {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_validate_ldap() {
+        assert!(validate_ldap(&ldap(serde_json::json!({
+            "server": "ldap.example.com",
+            "scheme": "ldaps",
+            "base_dn": "ou=people,dc=example,dc=com",
+            "bind_dn": "cn=reader,dc=example,dc=com",
+            "bind_password_secret": "ldap-reader"
+        })))
+        .is_ok());
+        assert!(validate_ldap(&ldap(serde_json::json!({
+            "server": "ldap.example.com",
+            "bind_prefix": "uid=",
+            "bind_suffix": ",ou=people,dc=example,dc=com"
+        })))
+        .is_ok());
+
+        // No mode, both modes, and half of the bind credentials
+        assert!(validate_ldap(&ldap(serde_json::json!({ "server": "ldap.example.com" }))).is_err());
+        assert!(validate_ldap(&ldap(serde_json::json!({
+            "server": "ldap.example.com",
+            "base_dn": "dc=example,dc=com",
+            "bind_prefix": "uid="
+        })))
+        .is_err());
+        assert!(validate_ldap(&ldap(serde_json::json!({
+            "server": "ldap.example.com",
+            "base_dn": "dc=example,dc=com",
+            "bind_dn": "cn=reader,dc=example,dc=com"
+        })))
+        .is_err());
+        assert!(validate_ldap(&ldap(serde_json::json!({
+            "server": "ldap.example.com",
+            "scheme": "https",
+            "base_dn": "dc=example,dc=com"
+        })))
+        .is_err());
+    }
+
+    #[test]
+    fn test_instance_parameter_conflicts() {
+        let parameters = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+
+        assert!(instance_parameter_conflicts(3, &parameters(&[("max_wal_senders", "10")])).is_empty());
+        assert_eq!(instance_parameter_conflicts(5, &parameters(&[("max_wal_senders", "2")])).len(), 1);
+        assert_eq!(instance_parameter_conflicts(4, &parameters(&[("max_replication_slots", "1")])).len(), 1);
+
+        // Single instance: no standby can ever acknowledge
+        assert_eq!(instance_parameter_conflicts(1, &parameters(&[("synchronous_standby_names", "*")])).len(), 1);
+        assert!(instance_parameter_conflicts(3, &parameters(&[("synchronous_standby_names", "ANY 2 (*)")])).is_empty());
+        assert_eq!(instance_parameter_conflicts(3, &parameters(&[("synchronous_standby_names", "FIRST 3 (a, b, c)")])).len(), 1);
+        assert_eq!(synchronous_standby_count("2 (a, b)"), 2);
+        assert_eq!(synchronous_standby_count("a, b"), 1);
+    }
+
+    #[test]
+    fn test_validate_cron_schedule() {
+        assert!(validate_cron_schedule("0 0 2 * * *").is_ok());
+        assert!(validate_cron_schedule("0 */15 8-18 ? * MON-FRI").is_ok());
+        assert!(validate_cron_schedule("0 30 1 1,15 jan,jul *").is_ok());
+        assert!(validate_cron_schedule("@daily").is_ok());
+        assert!(validate_cron_schedule("@every 6h").is_ok());
+        assert!(validate_cron_schedule("@every 1h30m").is_ok());
+
+        // Five-field crontab syntax is missing the seconds
+        assert!(validate_cron_schedule("0 2 * * *").is_err());
+        assert!(validate_cron_schedule("0 0 24 * * *").is_err());
+        assert!(validate_cron_schedule("0 0 2 0 * *").is_err());
+        assert!(validate_cron_schedule("0 */0 * * * *").is_err());
+        assert!(validate_cron_schedule("0 0 18-8 * * *").is_err());
+        assert!(validate_cron_schedule("@every soon").is_err());
+    }
+
+    #[test]
+    fn test_validate_backup_options() {
+        assert!(validate_backup_options(None, None).is_ok());
+        assert!(validate_backup_options(Some("volumeSnapshot"), Some("prefer-standby")).is_ok());
+        assert!(validate_backup_options(Some("pg_dump"), None).is_err());
+        assert!(validate_backup_options(None, Some("standby")).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use axum::http::{HeaderMap, Request, Response};
+use futures::future::BoxFuture;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::future::Future;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+tokio::task_local! {
+    static WARNINGS: RefCell<Vec<String>>;
+}
+
+/// Runs `future` with a fresh warning collector, so Kubernetes API calls made within it record their warnings.
+pub async fn scope<F: Future>(future: F) -> F::Output {
+    WARNINGS.scope(RefCell::new(Vec::new()), future).await
+}
+
+/// Drains the warnings collected so far in the current scope.
+pub fn take() -> Vec<String> {
+    WARNINGS
+        .try_with(|warnings| warnings.take())
+        .unwrap_or_default()
+}
+
+/// Adds the collected warnings to a response body as a `warnings` array, if there are any.
+pub fn attach(result: &mut Value) {
+    let warnings = take();
+    if warnings.is_empty() {
+        return;
+    }
+
+    if let Some(object) = result.as_object_mut() {
+        object.insert("warnings".to_string(), warnings.into());
+    }
+}
+
+/// Adds a warning of our own (not from the API server) to the current request's response.
+pub fn push(warning: String) {
+    tracing::warn!(warning = %warning, "Request warning");
+    let _ = WARNINGS.try_with(|collected| collected.borrow_mut().push(warning));
+}
+
+fn record(headers: &HeaderMap) {
+    let warnings: Vec<String> = headers
+        .get_all("warning")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .map(warning_text)
+        .collect();
+
+    if !warnings.is_empty() {
+        // Outside a scope (e.g. background tasks) the warnings are only logged
+        for warning in &warnings {
+            tracing::warn!(warning = %warning, "Kubernetes API warning");
+        }
+        let _ = WARNINGS.try_with(|collected| collected.borrow_mut().extend(warnings));
+    }
+}
+
+/// The message of a `Warning` header: `299 - "message"` becomes `message`.
+fn warning_text(header: &str) -> String {
+    let text = header
+        .strip_prefix("299 ")
+        .map(|rest| rest.trim_start_matches(|c: char| c != '"'))
+        .unwrap_or(header);
+
+    text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .unwrap_or(text)
+        .replace("\\\"", "\"")
+}
+
+/// Client middleware that records the `Warning` headers of every Kubernetes API response.
+#[derive(Clone)]
+pub struct WarningLayer;
+
+impl<S> Layer<S> for WarningLayer {
+    type Service = WarningService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        WarningService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct WarningService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for WarningService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let response = response.await?;
+            record(response.headers());
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use serde_json::json;
+
+    #[test]
+    fn test_warning_text() {
+        assert_eq!(
+            warning_text(r#"299 - "spec.foo is deprecated; use spec.bar""#),
+            "spec.foo is deprecated; use spec.bar"
+        );
+        assert_eq!(warning_text("plain text"), "plain text");
+    }
+
+    #[tokio::test]
+    async fn test_warnings_are_attached_within_scope() {
+        let result = scope(async {
+            let mut headers = HeaderMap::new();
+            headers.append("warning", HeaderValue::from_static(r#"299 - "first""#));
+            headers.append("warning", HeaderValue::from_static(r#"299 - "second""#));
+            record(&headers);
+
+            let mut result = json!({ "name": "db" });
+            attach(&mut result);
+            result
+        })
+        .await;
+
+        assert_eq!(result["warnings"], json!(["first", "second"]));
+
+        let mut result = json!({ "name": "db" });
+        scope(async { attach(&mut result) }).await;
+        assert!(result.get("warnings").is_none());
+    }
+}
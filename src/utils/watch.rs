@@ -0,0 +1,146 @@
+//! Streams of resource changes as Server-Sent Events, for dashboards that would otherwise poll the lists.
+
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures::{stream, Stream, StreamExt};
+use kube::runtime::{watcher, WatchStreamExt};
+use kube::{Api, Resource, ResourceExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::fmt::Debug;
+
+/// One change to a watched resource, sent as the data of an SSE event named after `type`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WatchEvent {
+    #[serde(rename = "type")]
+    pub type_: &'static str,
+    pub name: String,
+    pub namespace: Option<String>,
+}
+
+/// Turns watcher events into added/modified/deleted changes.
+///
+/// The watcher reports adds and modifications alike and re-lists after an expired watch, so the
+/// objects seen so far are tracked: a re-list reports what changed meanwhile, including deletions.
+#[derive(Debug, Default)]
+struct WatchState {
+    known: HashSet<(Option<String>, String)>,
+    relisted: Option<HashSet<(Option<String>, String)>>,
+}
+
+impl WatchState {
+    fn handle<K: Resource>(&mut self, event: watcher::Event<K>) -> Vec<WatchEvent> {
+        match event {
+            watcher::Event::Apply(object) => vec![self.apply(&object)],
+            watcher::Event::Delete(object) => {
+                self.known.remove(&key(&object));
+                vec![change("deleted", key(&object))]
+            }
+            watcher::Event::Init => {
+                self.relisted = Some(HashSet::new());
+                Vec::new()
+            }
+            watcher::Event::InitApply(object) => {
+                if let Some(relisted) = self.relisted.as_mut() {
+                    relisted.insert(key(&object));
+                }
+                vec![self.apply(&object)]
+            }
+            watcher::Event::InitDone => {
+                let relisted = self.relisted.take().unwrap_or_default();
+                let gone: Vec<_> = self.known.difference(&relisted).cloned().collect();
+                self.known = relisted;
+                gone.into_iter().map(|key| change("deleted", key)).collect()
+            }
+        }
+    }
+
+    fn apply<K: Resource>(&mut self, object: &K) -> WatchEvent {
+        let key = key(object);
+        let type_ = if self.known.insert(key.clone()) { "added" } else { "modified" };
+        change(type_, key)
+    }
+}
+
+fn key<K: Resource>(object: &K) -> (Option<String>, String) {
+    (object.namespace(), object.name_any())
+}
+
+fn change(type_: &'static str, (namespace, name): (Option<String>, String)) -> WatchEvent {
+    WatchEvent { type_, name, namespace }
+}
+
+/// Watches `api` and streams its changes as SSE until the client disconnects.
+///
+/// The watcher re-lists on its own when the watch expires; other API errors are logged and retried with backoff.
+pub fn sse<K>(api: Api<K>, config: watcher::Config) -> Sse<impl Stream<Item = Result<Event, Infallible>>>
+where
+    K: Resource + Clone + DeserializeOwned + Debug + Send + 'static,
+{
+    let mut state = WatchState::default();
+    let events = watcher(api, config)
+        .default_backoff()
+        .flat_map(move |event| {
+            let changes = match event {
+                Ok(event) => state.handle(event),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Watch failed, retrying");
+                    Vec::new()
+                }
+            };
+            stream::iter(changes)
+        })
+        .filter_map(|change| async move {
+            match Event::default().event(change.type_).json_data(&change) {
+                Ok(event) => Some(Ok(event)),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to serialize watch event");
+                    None
+                }
+            }
+        });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use kube::api::ObjectMeta;
+
+    fn config_map(name: &str) -> ConfigMap {
+        ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("team-a".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn types(changes: Vec<WatchEvent>) -> Vec<(&'static str, String)> {
+        changes.into_iter().map(|change| (change.type_, change.name)).collect()
+    }
+
+    #[test]
+    fn test_watch_state() {
+        let mut state = WatchState::default();
+
+        assert!(state.handle::<ConfigMap>(watcher::Event::Init).is_empty());
+        assert_eq!(types(state.handle(watcher::Event::InitApply(config_map("a")))), [("added", "a".to_string())]);
+        assert_eq!(types(state.handle(watcher::Event::InitApply(config_map("b")))), [("added", "b".to_string())]);
+        assert!(state.handle::<ConfigMap>(watcher::Event::InitDone).is_empty());
+
+        assert_eq!(types(state.handle(watcher::Event::Apply(config_map("a")))), [("modified", "a".to_string())]);
+        assert_eq!(types(state.handle(watcher::Event::Apply(config_map("c")))), [("added", "c".to_string())]);
+        assert_eq!(types(state.handle(watcher::Event::Delete(config_map("c")))), [("deleted", "c".to_string())]);
+
+        // After an expired watch, objects missing from the re-list were deleted meanwhile
+        state.handle::<ConfigMap>(watcher::Event::Init);
+        assert_eq!(types(state.handle(watcher::Event::InitApply(config_map("a")))), [("modified", "a".to_string())]);
+        assert_eq!(types(state.handle::<ConfigMap>(watcher::Event::InitDone)), [("deleted", "b".to_string())]);
+    }
+}